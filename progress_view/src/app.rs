@@ -1,236 +1,1426 @@
-use std::io::stdout;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::{self, Duration};
 
-use crossterm::cursor::MoveUp;
+use crossterm::cursor::{MoveDown, MoveUp};
+use crossterm::event::{Event, EventStream, KeyEvent};
 use crossterm::queue;
-use crossterm::style::Print;
 use crossterm::terminal::{Clear, ClearType};
-use futures::Future;
-use tokio::runtime::{Builder, Runtime};
+use futures::{Future, Stream, StreamExt};
+use tokio::runtime::{Builder, Handle, Runtime, RuntimeFlavor};
 
 use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio_util::sync::{CancellationToken, DropGuard};
+use unicode_width::UnicodeWidthStr;
 
+use crate::interactive::{self, Action, Navigator, RawMode};
+use crate::output::{
+    display_width, ellipsize_escaped, BeginSynchronizedUpdate, EndSynchronizedUpdate, Output,
+    Stdout,
+};
+use crate::summary::Summary;
+use crate::task::{Acquire, Dependencies, Limiter, TaskError, TaskHandle, TaskOptions};
+use crate::theme::Theme;
 use crate::update::{self, Update, WidgetUpdate};
-use crate::widget::Widget;
+use crate::widget::{Context, Outcome, State, Widget, WidgetType};
 
 #[derive(Debug)]
 pub struct App {
+    /// in the order theyre drawn, which isnt the order of their ids once children get added, use `widget` to look one up by id
     pub widgets: Vec<Widget>,
     pub reciever: Receiver<update::WidgetUpdate>,
     pub sender: Sender<update::WidgetUpdate>,
-    pub runtime: Runtime,
+    /// the runtime tasks get spawned onto
+    pub runtime: Handle,
+    /// the runtime behind `runtime` if the app made it itself, so it goes away with the app
+    owned: Option<Runtime>,
+    /// the id of every widget in `widgets`, in the same order
+    ids: Vec<usize>,
+    /// where the widget with a given id currently is in `widgets`
+    positions: HashMap<usize, usize>,
+    /// the next id to give out, shared with every `UpdateSender`
+    next_id: Arc<AtomicUsize>,
+    /// the ids of widgets whose task hasn't finished yet
+    tasks: HashSet<usize>,
+    /// the ids of every widget that has had a task
+    ran: HashSet<usize>,
+    /// whether to print a summary once the run is over
+    print_summary: bool,
+    /// the lines of the last frame, so only the ones that changed get redrawn
+    frame: Vec<String>,
+    /// the size of the output when the last frame was drawn
+    size: Option<(u16, u16)>,
+    /// where frames get drawn to
+    output: Box<dyn Output>,
+    mode: RenderMode,
+    /// the characters & colors widgets get drawn with
+    theme: Theme,
+    /// the state, message & number of attempts of every widget the last time plain mode
+    /// printed it
+    reported: HashMap<usize, (State, String, usize)>,
+    /// the rows of every table the last time plain mode printed it
+    tabled: HashMap<usize, Vec<Vec<String>>>,
+    /// the lines logged since plain mode last printed
+    logged: Vec<String>,
+    /// cancelled when the run is aborted, shared with every `UpdateSender`
+    cancel: CancellationToken,
+    /// the runtime outlives us, so this makes sure nothing is left running on it
+    _cancel_on_drop: DropGuard,
+    /// limits how many tasks can run at once
+    limit: Option<Limiter>,
+    /// limits how many tasks in a group can run at once
+    groups: HashMap<String, Limiter>,
+    /// which finished subtrees get folded into their parent
+    collapse: Collapse,
+    /// the keyboard controlled cursor, if interactive mode is on
+    navigator: Option<Navigator>,
+    /// set while the terminal is in raw mode for interactive mode
+    raw_mode: Option<RawMode>,
+    /// whether a Ctrl-C signal cancels the run
+    handle_ctrl_c: bool,
+}
+
+/// how the app draws its widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// live if the output is a terminal, plain otherwise
+    #[default]
+    Auto,
+    /// redraws every widget 10 times a second
+    Live,
+    /// only appends a line whenever a widget changes, for logs & CI
+    Plain,
+}
+
+/// which finished subtrees get folded into a summary on their parent,
+/// like "✓ Fetch repos (312 done, 2 failed) in 14.2s"
+///
+/// only live rendering collapses anything, plain mode prints everything as it happens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collapse {
+    /// always show every widget
+    #[default]
+    Never,
+    /// collapse every finished subtree
+    Done,
+    /// collapse finished subtrees, unless something in them failed
+    Succeeded,
+}
+
+/// returned by `App::run_until_done` when the run was cancelled before everything was done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aborted;
+
+impl Display for Aborted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the run was aborted")
+    }
+}
+
+impl std::error::Error for Aborted {}
+
+/// why `App::run_until_done` didnt finish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunError {
+    /// the run was cancelled before everything was done
+    Aborted,
+    /// the app spawns onto a current thread runtime, which only `Runtime::block_on` drives,
+    /// so its tasks would never finish
+    CurrentThread,
+}
+
+impl Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunError::Aborted => write!(f, "{Aborted}"),
+            RunError::CurrentThread => write!(
+                f,
+                "run_until_done cant drive a current thread runtime, use `runtime.block_on(app.run())`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<Aborted> for RunError {
+    fn from(_: Aborted) -> Self {
+        RunError::Aborted
+    }
+}
+
+tokio::task_local! {
+    /// the update sender of the task that is running, so logs can find their way to its widget
+    static CURRENT: UpdateSender;
 }
 
 #[derive(Clone)]
 pub struct UpdateSender {
     pub sender: Sender<update::WidgetUpdate>,
+    pub next_id: Arc<AtomicUsize>,
+    pub cancel: CancellationToken,
+    /// the id of the widget this sends updates to
     pub index: usize,
 }
 
 impl UpdateSender {
-    pub fn new(sender: Sender<update::WidgetUpdate>, index: usize) -> Self {
-        Self { sender, index }
+    pub fn new(
+        sender: Sender<update::WidgetUpdate>,
+        next_id: Arc<AtomicUsize>,
+        cancel: CancellationToken,
+        index: usize,
+    ) -> Self {
+        Self {
+            sender,
+            next_id,
+            cancel,
+            index,
+        }
+    }
+
+    /// whether the run has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// waits until the run is cancelled
+    pub async fn cancelled(&self) {
+        self.cancel.cancelled().await
+    }
+
+    /// the update sender of the task this is called from, if its called from inside of one
+    pub fn current() -> Option<UpdateSender> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// adds a line to the log of the widget
+    pub async fn log<T: Into<String>>(&self, line: T) {
+        self.send(Update::Log(line.into())).await;
+    }
+
+    /// adds a line to the log of the widget without waiting,
+    /// the line gets dropped if the app is too far behind on updates
+    pub fn try_log<T: Into<String>>(&self, line: T) {
+        let update = WidgetUpdate::new(Update::Log(line.into()), self.index);
+        self.sender.try_send(update).ok();
     }
 
     pub async fn send(&self, update: Update) {
         self.sender
             .send(WidgetUpdate::new(update, self.index))
             .await
-            .expect("channel is open");
+            // the app is gone, so there is nobody left to show the update to
+            .ok();
+    }
+
+    /// sets the progress of a progress widget
+    pub async fn set_progress(&self, progress: usize) {
+        self.send(Update::SetProgress(progress)).await;
+    }
+
+    /// adds to the progress of a progress widget
+    pub async fn increment_progress(&self, amount: usize) {
+        self.send(Update::IncrementProgress(amount)).await;
+    }
+
+    /// sets the total of a progress widget, turning an indeterminate one into a regular one
+    pub async fn set_total(&self, total: usize) {
+        self.send(Update::SetTotal(total)).await;
+    }
+
+    /// sets the progress of a percentage widget
+    pub async fn set_percentage(&self, progress: f32) {
+        self.send(Update::SetPercentage(progress)).await;
+    }
+
+    /// counts another item of a counter widget as having turned out as `outcome`
+    pub async fn count(&self, outcome: Outcome) {
+        self.send(Update::Count(outcome)).await;
+    }
+
+    /// adds to the bytes transferred by a bytes widget
+    pub async fn add_bytes(&self, amount: u64) {
+        self.send(Update::AddBytes(amount)).await;
+    }
+
+    /// sets the total of a bytes widget, `None` if its not known
+    pub async fn set_bytes_total(&self, total: Option<u64>) {
+        self.send(Update::SetBytesTotal(total)).await;
+    }
+
+    /// adds a row to the bottom of a table widget
+    pub async fn add_row<R: IntoIterator<Item = T>, T: Into<String>>(&self, row: R) {
+        let row = row.into_iter().map(Into::into).collect();
+        self.send(Update::AddRow(row)).await;
+    }
+
+    /// replaces the row of a table widget that starts with the same cell as `row`,
+    /// or adds it to the bottom if there isnt one yet
+    pub async fn set_row<R: IntoIterator<Item = T>, T: Into<String>>(&self, row: R) {
+        let row = row.into_iter().map(Into::into).collect();
+        self.send(Update::SetRow(row)).await;
+    }
+
+    /// adds a value to the end of a sparkline widget
+    pub async fn push_value(&self, value: f32) {
+        self.send(Update::PushValue(value)).await;
+    }
+
+    /// replaces all of the values of a sparkline widget
+    pub async fn set_values(&self, values: Vec<f32>) {
+        self.send(Update::SetValues(values)).await;
+    }
+
+    /// sets the value of the bar labelled `label` of a histogram widget, adding it if its new
+    pub async fn set_bar<T: Into<String>>(&self, label: T, value: f32) {
+        let label = label.into();
+        self.send(Update::SetBar { label, value }).await;
+    }
+
+    /// passes along every chunk of `stream`, adding it to the bytes transferred by the widget
+    /// as it comes in, like the body of a `reqwest` response
+    ///
+    /// ```ignore
+    /// sender.set_bytes_total(response.content_length()).await;
+    /// let mut body = pin!(sender.track_bytes(response.bytes_stream()));
+    /// while let Some(chunk) = body.next().await { ... }
+    /// ```
+    pub fn track_bytes<S, B, E>(&self, stream: S) -> impl Stream<Item = Result<B, E>>
+    where
+        S: Stream<Item = Result<B, E>>,
+        B: AsRef<[u8]>,
+    {
+        let sender = self.clone();
+        stream.then(move |chunk| {
+            let sender = sender.clone();
+            async move {
+                if let Ok(chunk) = &chunk {
+                    sender.add_bytes(chunk.as_ref().len() as u64).await;
+                }
+                chunk
+            }
+        })
+    }
+
+    /// adds `widget` as the last child of this widget
+    ///
+    /// returns a sender for the new widget
+    pub async fn add_child(&self, widget: Widget) -> UpdateSender {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let widget = Box::new(widget);
+        self.send(Update::AddChild { id, widget }).await;
+
+        Self::new(
+            self.sender.clone(),
+            self.next_id.clone(),
+            self.cancel.clone(),
+            id,
+        )
+    }
+}
+
+/// something a task can return
+///
+/// `()` always succeeds, and a `Result` fails with the `Display` of its error
+pub trait TaskOutput: Send + 'static {
+    type Output: Send + 'static;
+
+    fn into_result(self) -> Result<Self::Output, String>;
+}
+
+impl TaskOutput for () {
+    type Output = ();
+
+    fn into_result(self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+impl<T, E> TaskOutput for Result<T, E>
+where
+    T: Send + 'static,
+    E: Display + Send + 'static,
+{
+    type Output = T;
+
+    fn into_result(self) -> Result<T, String> {
+        self.map_err(|error| error.to_string())
     }
 }
 
 impl Default for App {
+    /// creates an app that spawns tasks onto the current runtime,
+    /// or onto a runtime of its own if there isnt a current runtime
     fn default() -> Self {
-        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        if let Ok(runtime) = Handle::try_current() {
+            return Self::new(runtime);
+        }
 
-        Self::new(runtime)
+        let owned = Builder::new_multi_thread().enable_all().build().unwrap();
+        let mut app = Self::new(owned.handle().clone());
+        app.owned = Some(owned);
+        app
     }
 }
 
 impl App {
-    pub fn new(runtime: Runtime) -> Self {
+    /// creates an app that spawns tasks onto `runtime`
+    ///
+    /// the runtime has to be driven for anything to happen, `App::run_until_done` only does
+    /// that for multi thread runtimes, await `App::run` on anything else
+    pub fn new(runtime: Handle) -> Self {
         let (sender, reciever) = mpsc::channel(64);
+        let cancel = CancellationToken::new();
 
         Self {
             widgets: vec![],
             reciever,
             sender,
             runtime,
+            owned: None,
+            ids: vec![],
+            positions: HashMap::new(),
+            next_id: Arc::new(AtomicUsize::new(0)),
+            tasks: HashSet::new(),
+            ran: HashSet::new(),
+            print_summary: false,
+            frame: vec![],
+            size: None,
+            output: Box::new(Stdout),
+            mode: RenderMode::Auto,
+            theme: Theme::default(),
+            collapse: Collapse::Never,
+            navigator: None,
+            raw_mode: None,
+            handle_ctrl_c: false,
+            reported: HashMap::new(),
+            tabled: HashMap::new(),
+            logged: vec![],
+            _cancel_on_drop: cancel.clone().drop_guard(),
+            cancel,
+            limit: None,
+            groups: HashMap::new(),
         }
     }
 
+    /// sets where the app draws to, stdout by default
+    pub fn set_output<O: Output + 'static>(&mut self, output: O) {
+        self.output = Box::new(output);
+    }
+
+    /// sets how the app draws its widgets, `RenderMode::Auto` by default
+    pub fn set_render_mode(&mut self, mode: RenderMode) {
+        self.mode = mode;
+    }
+
+    /// sets which finished subtrees get folded into their parent
+    pub fn set_collapse(&mut self, collapse: Collapse) {
+        self.collapse = collapse;
+    }
+
+    /// lets you move a cursor over the widgets with the arrow keys while the app runs,
+    /// expand & collapse subtrees, and press enter to see the details of a widget
+    ///
+    /// puts the terminal in raw mode while running, and keeps running until q is pressed.
+    /// only does anything when drawing live to a terminal
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.navigator = interactive.then(Navigator::default);
+    }
+
+    /// cancels the run when the process gets a Ctrl-C signal, off by default
+    ///
+    /// tokio never uninstalls its signal handler, so once the app has run Ctrl-C wont
+    /// stop the process anymore, only turn this on if thats fine for the rest of the program.
+    /// interactive mode gets Ctrl-C as a key press either way
+    pub fn set_handle_ctrl_c(&mut self, handle_ctrl_c: bool) {
+        self.handle_ctrl_c = handle_ctrl_c;
+    }
+
+    /// changes the characters & colors widgets get drawn with
+    ///
+    /// the colors get left out anyways if `NO_COLOR` is set
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme.respect_no_color();
+    }
+
+    /// whether we are redrawing frames or printing plain lines
+    fn is_plain(&self) -> bool {
+        match self.mode {
+            RenderMode::Auto => !self.output.is_terminal(),
+            RenderMode::Live => false,
+            RenderMode::Plain => true,
+        }
+    }
+
+    /// limits how many tasks can run at once
+    ///
+    /// tasks past the limit are queued & start in the order they were added,
+    /// only affects tasks added after this
+    pub fn set_concurrency_limit(&mut self, limit: usize) {
+        self.limit = Some(Limiter::new(limit));
+    }
+
+    /// limits how many tasks in `group` can run at once, on top of the app wide limit
+    ///
+    /// tasks join a group through `TaskOptions::group`, only affects tasks added after this
+    pub fn set_group_limit<T: Into<String>>(&mut self, group: T, limit: usize) {
+        self.groups.insert(group.into(), Limiter::new(limit));
+    }
+
+    /// creates a sender for the widget with the id `index`
+    pub fn update_sender(&self, index: usize) -> UpdateSender {
+        UpdateSender::new(
+            self.sender.clone(),
+            self.next_id.clone(),
+            self.cancel.clone(),
+            index,
+        )
+    }
+
+    /// the token that cancels the run, cancelling it stops every task
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// cancels every task & marks everything that isnt done yet as cancelled
+    pub fn cancel(&mut self) {
+        self.cancel.cancel();
+        for widget in &mut self.widgets {
+            if !widget.is_done() {
+                widget.set_cancelled();
+            }
+        }
+        self.tasks.clear();
+    }
+
+    /// whether to print a summary of how everything went once the run is over
+    pub fn set_print_summary(&mut self, print_summary: bool) {
+        self.print_summary = print_summary;
+    }
+
+    /// how the run has gone so far
+    ///
+    /// counts every widget that ran a task, and every other widget with a state of its own,
+    /// like the rows tasks add with `UpdateSender::add_child`. widgets that only group
+    /// others together take their state from them, so they arent counted again
+    pub fn summary(&self) -> Summary {
+        let now = time::Instant::now();
+        let mut summary = Summary::default();
+        let mut slowest = vec![];
+        let mut first = None::<time::Instant>;
+        let mut last = None::<time::Instant>;
+
+        for (position, (widget, id)) in self.widgets.iter().zip(&self.ids).enumerate() {
+            if !widget.attempts.is_empty() {
+                let attempts = widget.attempts.clone();
+                summary.attempts.push((widget.message.clone(), attempts));
+            }
+            if !widget.logs.is_empty() {
+                let logs = widget.logs.iter().cloned().collect();
+                summary.logs.push((widget.message.clone(), logs));
+            }
+            let groups = self
+                .widgets
+                .get(position + 1)
+                .is_some_and(|child| child.indent > widget.indent);
+            if widget.is_static() || (groups && !self.ran.contains(id)) {
+                continue;
+            }
+
+            match widget.state() {
+                State::Done => summary.succeeded += 1,
+                State::Failed => {
+                    summary.failed += 1;
+                    summary.errors.push(widget.message.clone());
+                }
+                State::Cancelled => summary.cancelled += 1,
+                State::Skipped => summary.skipped += 1,
+                _ => {}
+            }
+            if let Some(started) = widget.started {
+                let finished = widget.finished.unwrap_or(now);
+                first = Some(first.map_or(started, |first| first.min(started)));
+                last = Some(last.map_or(finished, |last| last.max(finished)));
+                slowest.push((widget.message.clone(), finished - started));
+            }
+        }
+
+        if let (Some(first), Some(last)) = (first, last) {
+            summary.elapsed = last.saturating_duration_since(first);
+        }
+        slowest.sort_by(|(_, a), (_, b)| b.cmp(a));
+        slowest.truncate(Summary::SLOWEST);
+        summary.slowest = slowest;
+        summary
+    }
+
+    /// prints the summary under the last frame, if thats turned on
+    fn finish(&mut self) -> Summary {
+        let summary = self.summary();
+        if self.print_summary {
+            write!(self.output, "{summary}").expect("no io err");
+            self.output.flush().expect("no io err");
+            // the summary is in the way of drawing over the last frame now
+            self.frame.clear();
+        }
+        summary
+    }
+
+    /// gets the widget with the id `index`
+    pub fn widget(&self, index: usize) -> Option<&Widget> {
+        self.positions
+            .get(&index)
+            .map(|&position| &self.widgets[position])
+    }
+
     /// adds a new task to the runtime
-    pub fn add_task<E, F>(&mut self, f: E, index: usize)
+    ///
+    /// the task can either return `()` or a `Result`, if it returns an error
+    /// the widget is turned into an error widget displaying it
+    ///
+    /// returns a handle to the output of the task, which can be awaited by other tasks
+    /// or collected once the app is done running
+    pub fn add_task<E, F>(
+        &mut self,
+        f: E,
+        index: usize,
+    ) -> TaskHandle<<F::Output as TaskOutput>::Output>
     where
         E: FnOnce(UpdateSender) -> F + Send + 'static,
-        F: Future<Output = ()> + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: TaskOutput,
     {
-        let sender = UpdateSender::new(self.sender.clone(), index);
-        self.runtime.spawn(async move {
-            // set active to true so i get the cool spinner thingy
-            sender.send(Update::SetActive).await;
-            // run the actual task & pass it into output
-            f(sender.clone()).await;
-            // set done
-            sender.send(Update::SetDone).await;
-        });
+        self.spawn_task(index, None, (), async move |sender, ()| {
+            f(sender).await.into_result().map_err(TaskError::Failed)
+        })
+    }
+
+    /// adds a new task to the runtime that gets run according to `options`
+    ///
+    /// if an attempt fails or times out `f` gets called again to retry it,
+    /// up until `options.max_attempts`
+    pub fn add_task_with<E, F>(
+        &mut self,
+        f: E,
+        index: usize,
+        options: TaskOptions,
+    ) -> TaskHandle<<F::Output as TaskOutput>::Output>
+    where
+        E: Fn(UpdateSender) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: TaskOutput,
+    {
+        let group = options.group.clone();
+        self.spawn_task(index, group.as_deref(), (), async move |sender, ()| {
+            options.run(f, sender).await.map_err(TaskError::Failed)
+        })
+    }
+
+    /// adds a new task to the runtime that only starts once every task in `dependencies` is done
+    ///
+    /// `f` gets passed the outputs of the dependencies, and if any of them
+    /// didnt succeed the task is skipped instead
+    ///
+    /// returns a handle that later tasks can depend on in turn
+    pub fn add_task_after<D, E, F>(
+        &mut self,
+        dependencies: D,
+        f: E,
+        index: usize,
+    ) -> TaskHandle<<F::Output as TaskOutput>::Output>
+    where
+        D: Dependencies,
+        E: FnOnce(UpdateSender, D::Output) -> F + Send + 'static,
+        F: Future + Send + 'static,
+        F::Output: TaskOutput,
+    {
+        self.spawn_task(index, None, dependencies, async move |sender, outputs| {
+            f(sender, outputs)
+                .await
+                .into_result()
+                .map_err(TaskError::Failed)
+        })
+    }
+
+    /// spawns `f` onto the runtime once `dependencies` are done
+    /// & keeps the widget with the id `index` in sync with it
+    ///
+    /// if a dependency didnt succeed the task is skipped before it takes a slot in any limiter
+    fn spawn_task<D, E, F, T>(
+        &mut self,
+        index: usize,
+        group: Option<&str>,
+        dependencies: D,
+        f: E,
+    ) -> TaskHandle<T>
+    where
+        D: Dependencies,
+        E: FnOnce(UpdateSender, D::Output) -> F + Send + 'static,
+        F: Future<Output = Result<T, TaskError>> + Send + 'static,
+        T: Send + 'static,
+    {
+        let sender = self.update_sender(index);
+        let handle = TaskHandle::new(self.widget(index).map_or("", |w| &w.message));
+        self.tasks.insert(index);
+        self.ran.insert(index);
+
+        // the group line comes first, then the app wide one
+        let limiters = group
+            .and_then(|group| self.groups.get(group))
+            .into_iter()
+            .chain(&self.limit)
+            .cloned()
+            .collect::<Vec<_>>();
+        // get in the first line now so tasks start in the order they were added,
+        // the rest we get into once we are actually ready to go
+        let waiting = dependencies.dependencies();
+        let mut early = match waiting.is_empty() {
+            true => limiters.first().map(Limiter::acquire),
+            false => None,
+        };
+
+        let out = handle.clone();
+
+        let current = sender.clone();
+        let task = async move {
+            let wait = async |acquire: Acquire| {
+                if acquire.is_queued() {
+                    sender.send(Update::SetQueued).await;
+                }
+                acquire.wait().await
+            };
+
+            // run the actual task & pass it into output, unless we get cancelled first
+            let cancel = sender.cancel.clone();
+            let output = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => Err(TaskError::Cancelled),
+                output = async {
+                    // owned rather than borrowed so it doesnt have to be Sync to hold across awaits
+                    let dependencies = dependencies;
+                    loop {
+                        let pending = waiting
+                            .iter()
+                            .filter(|dependency| !dependency.is_done())
+                            .collect::<Vec<_>>();
+                        let Some(next) = pending.first() else {
+                            break;
+                        };
+
+                        let names = pending.iter().map(|d| d.name.as_str()).collect::<Vec<_>>();
+                        let status = format!("waiting on {}", names.join(", "));
+                        sender.send(Update::SetStatus(Some(status))).await;
+
+                        // no point waiting on the rest if this one failed
+                        if !next.wait().await {
+                            break;
+                        }
+                    }
+                    if !waiting.is_empty() {
+                        sender.send(Update::SetStatus(None)).await;
+                    }
+                    let outputs = dependencies.outputs()?;
+
+                    let mut slots = vec![];
+                    for limiter in &limiters {
+                        let acquire = early.take().unwrap_or_else(|| limiter.acquire());
+                        slots.push(wait(acquire).await);
+                    }
+
+                    // set active to true so i get the cool spinner thingy
+                    sender.send(Update::SetActive).await;
+                    f(sender.clone(), outputs).await
+                } => output,
+            };
+
+            let update = match &output {
+                // set done
+                Ok(_) => Update::SetDone,
+                Err(TaskError::Failed(error)) => Update::SetError(error.clone()),
+                Err(TaskError::Cancelled) => Update::SetCancelled,
+                Err(TaskError::Skipped(reason)) => Update::SetSkipped(reason.clone()),
+            };
+            out.finish(output);
+            sender.send(update).await;
+        };
+        self.runtime.spawn(CURRENT.scope(current, task));
+
+        handle
     }
 
     /// adds a widget to the app
     ///
-    /// returns the index of the widget that you just added
+    /// returns the id of the widget that you just added, which is also its index
+    /// until a task adds children above it
     pub fn add_widget(&mut self, widget: Widget) -> usize {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.positions.insert(id, self.widgets.len());
+        self.ids.push(id);
         self.widgets.push(widget);
-        return self.widgets.len() - 1;
+        id
     }
 
     /// adds widgets to the app
     ///
-    /// returns the index of the first widget you added
+    /// returns the id of the first widget you added, the rest follow on from it
     pub fn add_widgets(&mut self, widgets: impl IntoIterator<Item = Widget>) -> usize {
-        let out = self.widgets.len();
-        self.widgets.extend(widgets);
+        let mut widgets = widgets.into_iter();
+        let Some(first) = widgets.next() else {
+            return self.next_id.load(Ordering::Relaxed);
+        };
+
+        let out = self.add_widget(first);
+        for widget in widgets {
+            self.add_widget(widget);
+        }
         out
     }
 
+    /// inserts `widget` with the id `id` as the last child of the widget with the id `parent`
+    fn add_child(&mut self, parent: usize, id: usize, mut widget: Widget) {
+        let parent = self.positions[&parent];
+        let indent = self.widgets[parent].indent;
+        widget.indent = indent + 1;
+
+        // skip over the existing children
+        let mut index = parent + 1;
+        while index < self.widgets.len() && self.widgets[index].indent > indent {
+            index += 1;
+        }
+
+        self.widgets.insert(index, widget);
+        self.ids.insert(index, id);
+        for (position, id) in self.ids.iter().enumerate().skip(index) {
+            self.positions.insert(*id, position);
+        }
+    }
+
     /// does a recursive depth first search through the widget tree
     /// to display them & sets the widget as done if all of its children are done
     ///
     /// returns whether everything is done
     pub fn render(&mut self) -> bool {
         // handle all the updates
-        while let Some(update) = self.reciever.try_recv().ok() {
+        let plain = self.is_plain();
+        while let Ok(update) = self.reciever.try_recv() {
             use Update::*;
-            let widget = &mut self.widgets[update.index];
+            // an id this app never handed out, theres nothing to update
+            let Some(&position) = self.positions.get(&update.index) else {
+                continue;
+            };
+            let widget = &mut self.widgets[position];
 
             match update.update_type {
                 SetActive => {
-                    widget.active = true;
+                    widget.activate();
+                    widget.queued = false;
+                }
+                SetQueued => widget.queued = true,
+                SetDone => {
+                    widget.set_done();
+                    self.tasks.remove(&update.index);
+                }
+                SetError(error) => {
+                    widget.set_error(error);
+                    self.tasks.remove(&update.index);
+                }
+                SetCancelled => {
+                    widget.set_cancelled();
+                    self.tasks.remove(&update.index);
+                }
+                SetSkipped(reason) => {
+                    widget.set_skipped(reason);
+                    self.tasks.remove(&update.index);
                 }
-                SetDone => widget.set_done(),
                 SetMessage(message) => {
                     widget.message = message;
                 }
+                SetProgress(progress) => widget.update_discrete_progress(progress),
+                IncrementProgress(amount) => widget.increment_progress(amount),
+                SetTotal(total) => widget.update_total(total),
+                SetPercentage(progress) => widget.update_progress(progress),
+                Count(outcome) => widget.count(outcome),
+                AddBytes(amount) => widget.add_bytes(amount),
+                SetBytesTotal(total) => widget.update_bytes_total(total),
+                AddRow(row) => widget.add_row(row),
+                SetRow(row) => widget.set_row(row),
+                PushValue(value) => widget.push_value(value),
+                SetValues(values) => widget.set_values(values),
+                SetBar { label, value } => widget.set_bar(label, value),
+                AddChild { id, widget } => self.add_child(update.index, id, *widget),
+                SetStatus(status) => widget.status = status,
+                AddAttempt(error) => widget.attempts.push(error),
+                Log(line) => {
+                    if plain {
+                        let pad = "  ".repeat(widget.indent);
+                        for line in line.lines() {
+                            let logged = format!("{pad}[log] {}: {line}", widget.message);
+                            self.logged.push(logged);
+                        }
+                    }
+                    widget.log(line);
+                }
             }
         }
 
         // queue!(stdout(), crossterm::cursor::Hide).expect("no io err");
-        let out = self.update_widget_status(0).all_done;
-        self.render_widgets();
+        let out = self.update_widgets();
+        if self.is_plain() {
+            self.render_plain();
+        } else {
+            self.render_widgets(out);
+        }
 
         out
     }
 
-    fn render_widgets(&self) {
+    fn render_widgets(&mut self, done: bool) {
         let time = time::SystemTime::now();
+        let now = time::Instant::now();
+
+        // lines that used to fit might have wrapped onto more rows, so move up over
+        // however many the last frame takes up now & draw it again from scratch
+        let size = self.output.size();
+        if size != self.size && !self.frame.is_empty() {
+            let rows = match size {
+                Some((columns, _)) => self
+                    .frame
+                    .iter()
+                    .map(|line| {
+                        let width = display_width(line);
+                        (width.max(1) - 1) / (columns as usize).max(1) + 1
+                    })
+                    .sum(),
+                None => self.frame.len(),
+            };
+            queue!(
+                self.output,
+                MoveUp(rows as u16),
+                Clear(ClearType::FromCursorDown)
+            )
+            .expect("no io err");
+            self.frame.clear();
+        }
+        self.size = size;
+        // leave the last column free so the cursor never wraps onto the next line
+        let columns = size.map(|(columns, _)| (columns as usize).saturating_sub(1));
 
-        queue!(stdout(), MoveUp(self.widgets.len() as u16),).expect("no io err");
+        let navigator = self.navigator.as_ref();
+        let selected = navigator.and_then(|navigator| navigator.selected);
+        let details = navigator.is_some_and(|navigator| navigator.details);
+        let details = selected
+            .and_then(|selected| self.widget(selected))
+            .filter(|_| details);
+        // the details get the right half of the screen, after the separator
+        let separator = self.theme.glyphs.separator.width() + 1;
+        let (columns, pane_width) = match (columns, details) {
+            (Some(columns), Some(_)) => (
+                Some(columns / 2),
+                (columns - columns / 2).saturating_sub(separator),
+            ),
+            (columns, _) => (columns, 60),
+        };
+        let tree_width = columns;
+        // room for the cursor in front of every widget
+        let gutter = navigator.map_or(0, |_| self.theme.glyphs.cursor.width() + 1);
+        let columns = columns.map(|columns| columns.saturating_sub(gutter));
+
+        let mut shown = vec![];
+        let mut rows = vec![];
+        let mut widgets = self.widgets.iter().zip(&self.ids).peekable();
+        while let Some((widget, &id)) = widgets.next() {
+            // the children of collapsed widgets are in the summary instead
+            let hidden = match navigator {
+                Some(navigator) => navigator.hides_children(id, widget),
+                None => widget.summary.is_some(),
+            };
+            if hidden {
+                while widgets
+                    .next_if(|(child, _)| child.indent > widget.indent)
+                    .is_some()
+                {}
+            }
 
-        for widget in &self.widgets {
+            let width = columns.map(|columns| columns.saturating_sub(widget.indent * 3));
+            let mut lines = widget.render(&Context::new(time, width, &self.theme));
             // put the backing behind it
             if widget.indent > 0 {
-                queue!(
-                    stdout(),
-                    Clear(ClearType::CurrentLine),
-                    Print(" ".repeat(widget.indent * 3 - 2) + "• ")
-                )
-                .expect("no io err")
+                let bullet = self.theme.glyphs.bullet;
+                lines[0] = format!("{}{bullet} {}", " ".repeat(widget.indent * 3 - 2), lines[0]);
             }
+            if navigator.is_some() {
+                let cursor = self.theme.glyphs.cursor;
+                for (n, line) in lines.iter_mut().enumerate() {
+                    *line = match n == 0 && selected == Some(id) {
+                        true => format!("{cursor} {line}"),
+                        false => format!("{:gutter$}{line}", ""),
+                    };
+                }
+            }
+            shown.push((widget, id));
+            rows.push(lines);
+        }
 
-            widget.render(time);
+        let mut footer = vec![];
+        if navigator.is_some() && done {
+            footer.push(
+                self.theme
+                    .paint("everything is done, press q to quit", self.theme.colors.dim),
+            );
+        }
+
+        // the line after the frame is where the cursor ends up, so it has to fit too
+        let height = size.map(|(_, height)| (height as usize).saturating_sub(1 + footer.len()));
+        let selected = shown.iter().position(|&(_, id)| Some(id) == selected);
+        let widgets = shown.iter().map(|&(widget, _)| widget).collect::<Vec<_>>();
+        let mut lines = match height {
+            Some(height) => viewport(&widgets, rows, height, selected, &self.theme),
+            None => rows.concat(),
+        };
+        // bars dont shrink past their label, so cut off whatever is left over
+        // instead of letting it wrap onto another line
+        if let Some(width) = tree_width {
+            let ellipsis = self.theme.glyphs.ellipsis;
+            for line in lines.iter_mut().chain(&mut footer) {
+                *line = ellipsize_escaped(line, width, ellipsis);
+            }
         }
-    }
 
-    pub fn update_widgets(&mut self) {
-        self.update_widget_status(0);
+        if let Some(widget) = details {
+            let mut pane = interactive::details(widget, pane_width, now);
+            let height = height.unwrap_or(usize::MAX);
+            pane.truncate(height);
+            lines.resize(lines.len().max(pane.len()), String::new());
+            pane.resize(lines.len(), String::new());
+
+            let tree_width = tree_width.unwrap_or_else(|| {
+                let width = lines.iter().map(|line| display_width(line));
+                width.max().unwrap_or_default()
+            });
+            let separator = self.theme.glyphs.separator;
+            for (line, pane) in lines.iter_mut().zip(pane) {
+                let pad = tree_width.saturating_sub(display_width(line));
+                *line = format!("{line}{:pad$}{separator} {pane}", "");
+            }
+        }
+        lines.extend(footer);
+
+        if let Some(navigator) = &mut self.navigator {
+            navigator.visible = shown.iter().map(|&(_, id)| id).collect();
+        }
+        self.draw(lines);
     }
 
-    fn update_widget_status(&mut self, mut index: usize) -> RenderInner {
-        let mut prev = index;
-        let indent = self.widgets[index].indent;
+    /// prints a line for everything that changed since the last time this was called
+    fn render_plain(&mut self) {
+        let mut lines = vec![];
+        lines.append(&mut self.logged);
+        let theme = self.theme.without_colors();
+        for (widget, id) in self.widgets.iter().zip(&self.ids) {
+            let state = widget.state();
+            let pad = "  ".repeat(widget.indent);
+            // charts get printed out whole whenever they change
+            let chart;
+            let message = match widget.widget {
+                WidgetType::Sparkline { .. } | WidgetType::Histogram { .. } => {
+                    let ctx = Context::new(time::SystemTime::now(), None, &theme);
+                    let lines = widget.render(&ctx);
+                    let lines = lines
+                        .iter()
+                        .map(|line| line.trim_start())
+                        .collect::<Vec<_>>();
+                    chart = lines.join(&format!("\n{pad}"));
+                    &chart
+                }
+                _ => &widget.message,
+            };
 
-        let mut out = RenderInner {
-            new_index: index,
-            all_done: true,
-            active: false,
-        };
-        let mut children = false;
+            let (last_state, last_message, last_attempts) = match self.reported.get(id) {
+                Some((state, message, attempts)) => (Some(*state), Some(message), *attempts),
+                None => (None, None, 0),
+            };
+            let message_changed = last_message != Some(message);
 
-        macro ret() {{
-            if children {
-                let widget = &mut self.widgets[prev];
-                if out.all_done {
-                    widget.set_done()
+            let event = match widget.widget {
+                // text has no state, so all thats left to say is what it says
+                WidgetType::Text | WidgetType::Sparkline { .. } | WidgetType::Histogram { .. }
+                    if message_changed =>
+                {
+                    lines.push(format!("{pad}{message}"));
+                    None
                 }
-                if out.active {
-                    widget.active = true;
+                WidgetType::Text | WidgetType::Sparkline { .. } | WidgetType::Histogram { .. } => {
+                    None
                 }
-            }
-            out
-        }}
-
-        while index < self.widgets.len() {
-            let widget = &self.widgets[index];
-
-            use std::cmp::Ordering::*;
-            match widget.indent.cmp(&indent) {
-                // if less, we should return
-                Less => {
-                    out.new_index = index;
-                    return ret!();
-                }
-                // if equal, keep going
-                Equal => {
-                    if !widget.is_done() {
-                        out.all_done = false;
-                        // dbg!(&widget);
+                // the header goes out once, & after that every row thats new or changed
+                WidgetType::Table(ref table) => {
+                    if message_changed {
+                        lines.push(format!("{pad}{message}"));
+                    }
+                    if last_message.is_none() {
+                        let headers = table.columns.iter().map(|column| column.header.as_str());
+                        lines.push(format!("{pad}{}", headers.collect::<Vec<_>>().join("  ")));
                     }
-                    if widget.is_active() {
-                        out.active = true;
+                    let printed = self.tabled.entry(*id).or_default();
+                    for row in table.rows.iter().filter(|row| !printed.contains(row)) {
+                        lines.push(format!("{pad}{}", row.join("  ")));
                     }
+                    *printed = table.rows.clone();
+                    None
                 }
-                // greater, this is out of our hands
-                // we should call render_inner on this widget
-                Greater => {
-                    children = true;
+                _ if last_state != Some(state) => match state {
+                    State::Inactive => None,
+                    State::Queued => Some("queued"),
+                    State::Active => Some("started"),
+                    State::Done => Some("done"),
+                    State::Failed => Some("failed"),
+                    State::Cancelled => Some("cancelled"),
+                    State::Skipped => Some("skipped"),
+                },
+                _ if message_changed && state == State::Active => Some("message"),
+                _ => None,
+            };
+            // every failed attempt gets a line as soon as it fails, before whatever came after it
+            for error in widget.attempts.iter().skip(last_attempts) {
+                lines.push(format!("{pad}[retry] {}: {error}", widget.message));
+            }
+            if let Some(event) = event {
+                lines.push(format!("{pad}[{event}] {message}"));
+            }
 
-                    let ret = self.update_widget_status(index);
-                    // dbg!(&ret);
+            // inactive widgets havent been said anything about yet
+            if state != State::Inactive || widget.is_static() {
+                let attempts = widget.attempts.len();
+                self.reported
+                    .insert(*id, (state, message.clone(), attempts));
+            }
+        }
 
-                    if ret.all_done {
-                        self.widgets[prev].set_done();
-                    } else {
-                        out.all_done = false;
-                    }
-                    if ret.active {
-                        self.widgets[prev].active = true;
-                        out.active = true;
-                    }
+        for line in lines {
+            writeln!(self.output, "{line}").expect("no io err");
+        }
+        self.output.flush().expect("no io err");
+    }
 
-                    prev = index;
-                    index = ret.new_index;
-                }
+    /// draws over the last frame with `lines`, only rewriting the lines that changed
+    fn draw(&mut self, lines: Vec<String>) {
+        if lines == self.frame {
+            return;
+        }
+
+        let synchronized = self.output.synchronized_updates();
+        if synchronized {
+            queue!(self.output, BeginSynchronizedUpdate).expect("no io err");
+        }
+        if !self.frame.is_empty() {
+            queue!(self.output, MoveUp(self.frame.len() as u16)).expect("no io err");
+        }
+
+        // how many unchanged lines the cursor has to get past before the next changed one
+        let mut skipped = 0;
+        for (n, line) in lines.iter().enumerate() {
+            if self.frame.get(n) == Some(line) {
+                skipped += 1;
+                continue;
+            }
+            if skipped > 0 {
+                queue!(self.output, MoveDown(skipped)).expect("no io err");
+                skipped = 0;
+            }
+            queue!(self.output, Clear(ClearType::UntilNewLine)).expect("no io err");
+            // raw mode doesnt go back to the start of the line by itself
+            match self.raw_mode {
+                Some(_) => write!(self.output, "{line}\r\n").expect("no io err"),
+                None => writeln!(self.output, "{line}").expect("no io err"),
             }
+        }
+        if skipped > 0 {
+            queue!(self.output, MoveDown(skipped)).expect("no io err");
+        }
+        if lines.len() < self.frame.len() {
+            queue!(self.output, Clear(ClearType::FromCursorDown)).expect("no io err");
+        }
 
-            index += 1;
+        if synchronized {
+            queue!(self.output, EndSynchronizedUpdate).expect("no io err");
+        }
+        self.output.flush().expect("no io err");
+        self.frame = lines;
+    }
+
+    /// updates the status of every widget in the tree
+    ///
+    /// returns whether everything is done
+    pub fn update_widgets(&mut self) -> bool {
+        let mut index = 0;
+        let mut all_done = true;
+
+        while index < self.widgets.len() {
+            let ret = self.update_widget_status(index);
+            all_done &= ret.all_done;
+            index = ret.new_index;
+        }
+        all_done
+    }
+
+    /// does a recursive depth first search through the subtree starting at `index`
+    /// and sets the widget as done if all of its children are done
+    fn update_widget_status(&mut self, index: usize) -> RenderInner {
+        let indent = self.widgets[index].indent;
+
+        let mut all_done = true;
+        let mut active = false;
+        let mut failed = false;
+        let mut children = false;
+        let mut tally = Tally::default();
+
+        // everything indented further than us until the next sibling is a child
+        let mut child = index + 1;
+        while child < self.widgets.len() && self.widgets[child].indent > indent {
+            children = true;
+
+            let ret = self.update_widget_status(child);
+            all_done &= ret.all_done;
+            active |= ret.active;
+            failed |= ret.failed;
+            tally.count(&self.widgets[child], ret.failed);
+
+            child = ret.new_index;
+        }
+
+        let running = self.tasks.contains(&self.ids[index]);
+        let widget = &mut self.widgets[index];
+        if children {
+            // the widgets own task might still add more children
+            if all_done && !running {
+                widget.set_done();
+            }
+            if active {
+                widget.activate();
+            }
+            widget.children_failed = failed;
+
+            let collapse = match self.collapse {
+                Collapse::Never => false,
+                Collapse::Done => true,
+                Collapse::Succeeded => !failed,
+            };
+            // text & tables have nothing to show the summary next to
+            let collapse = collapse && !widget.is_static() && widget.state() == State::Done;
+            widget.summary = collapse.then(|| tally.to_string());
+        }
+
+        RenderInner {
+            new_index: child,
+            all_done: all_done && widget.is_done(),
+            active: widget.is_active(),
+            failed: failed || widget.widget == WidgetType::Error,
         }
-        return ret!();
     }
 
     /// runs the render function 10 times a second until everything is done
-    pub fn run_until_done(&mut self) {
-        print!("{}", "\n".repeat(self.widgets.len() + 1));
+    ///
+    /// blocks the current thread, so this cant be called from inside of an async context,
+    /// use `App::run` there instead
+    ///
+    /// returns `RunError::CurrentThread` if the app spawns onto a current thread runtime,
+    /// use `runtime.block_on(app.run())` for those instead
+    pub fn run_until_done(&mut self) -> Result<Summary, RunError> {
+        let runtime = self.runtime.clone();
+        if runtime.runtime_flavor() == RuntimeFlavor::CurrentThread {
+            return Err(RunError::CurrentThread);
+        }
+        Ok(runtime.block_on(self.run())?)
+    }
 
-        let runtime = &self.runtime as *const Runtime as *mut Runtime;
+    /// runs the render function 10 times a second until everything is done
+    ///
+    /// stops early if the run gets cancelled, either through the cancellation token
+    /// or by pressing Ctrl-C if `App::set_handle_ctrl_c` is on, `App::summary` still says
+    /// how far it got
+    pub async fn run(&mut self) -> Result<Summary, Aborted> {
+        let mut interval = tokio::time::interval(Duration::from_secs_f32(0.1));
+        let cancel = self.cancel.clone();
+        let handle_ctrl_c = self.handle_ctrl_c;
+        let ctrl_c = async move {
+            // without a handler theres nothing to wait for
+            if !handle_ctrl_c || tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        tokio::pin!(ctrl_c);
 
-        unsafe { runtime.as_mut().unwrap() }.block_on(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs_f32(0.1));
+        // only interactive mode reads keys, everything else picks up resizes
+        // by checking the size of the output every render
+        let listen = !self.is_plain()
+            && self.navigator.is_some()
+            && self.output.size().is_some()
+            && io::stdin().is_terminal();
+        let mut events = listen.then(EventStream::new);
+        if listen {
+            self.raw_mode = RawMode::enable();
+        }
+        // interactive mode keeps going until its told to quit
+        let interactive = self.raw_mode.is_some();
+        let mut quit = false;
 
-            while !self.render() {
-                interval.tick().await;
+        loop {
+            let done = self.render();
+            if done && (!interactive || quit) {
+                break;
             }
-        });
+
+            tokio::select! {
+                _ = interval.tick() => {}
+                event = next_event(&mut events) => match event {
+                    Some(Ok(Event::Key(key))) => match self.handle_key(key) {
+                        Action::Cancel => cancel.cancel(),
+                        // quitting before everything is done cancels whatever is left
+                        Action::Quit if done => quit = true,
+                        Action::Quit => cancel.cancel(),
+                        Action::Redraw | Action::Nothing => {}
+                    },
+                    // the next render picks up the new size after a resize
+                    Some(Ok(_)) => {}
+                    // something went wrong reading events, stop listening
+                    Some(Err(_)) | None => events = None,
+                },
+                _ = &mut ctrl_c => cancel.cancel(),
+                _ = cancel.cancelled() => {}
+            }
+
+            if cancel.is_cancelled() {
+                // draw the cancelled widgets so we dont leave a half finished frame behind
+                self.cancel();
+                self.render();
+                self.raw_mode = None;
+                self.finish();
+                return Err(Aborted);
+            }
+        }
+        self.raw_mode = None;
+        Ok(self.finish())
+    }
+
+    /// moves the cursor in interactive mode
+    fn handle_key(&mut self, key: KeyEvent) -> Action {
+        match &mut self.navigator {
+            Some(navigator) => navigator.handle_key(key, &self.widgets, &self.ids),
+            None => Action::Nothing,
+        }
+    }
+}
+
+/// fits the rendered `rows` of `widgets` into `height` lines
+///
+/// if they dont all fit, the selected widget is kept first, then failed & running widgets,
+/// then whatever hasnt finished yet, and everything left over gets folded into
+/// a "… 143 more (120 done)" line
+fn viewport(
+    widgets: &[&Widget],
+    rows: Vec<Vec<String>>,
+    height: usize,
+    selected: Option<usize>,
+    theme: &Theme,
+) -> Vec<String> {
+    if rows.iter().map(Vec::len).sum::<usize>() <= height {
+        return rows.concat();
+    }
+
+    let priority = |widget: &Widget| match widget.state() {
+        State::Failed | State::Active => 0,
+        _ if widget.children_failed => 0,
+        State::Inactive | State::Queued => 1,
+        State::Done | State::Cancelled | State::Skipped => 2,
+    };
+    let mut order = (0..widgets.len()).collect::<Vec<_>>();
+    // the selected widget always stays on screen
+    order.sort_by_key(|&index| (Some(index) != selected, priority(widgets[index])));
+
+    // leave a line for saying how much got folded away
+    let mut room = height.saturating_sub(1);
+    let mut shown = vec![0; rows.len()];
+    for index in order {
+        let lines = rows[index].len();
+        // drop the attempts if the whole thing doesnt fit
+        shown[index] = match room {
+            _ if lines <= room => lines,
+            1.. => 1,
+            0 => continue,
+        };
+        room -= shown[index];
+    }
+
+    let mut lines = vec![];
+    let mut hidden = 0;
+    let mut done = 0;
+    for ((widget, rows), shown) in widgets.iter().zip(rows).zip(shown) {
+        if shown == 0 {
+            hidden += 1;
+            done += (widget.state() == State::Done) as usize;
+        }
+        lines.extend(rows.into_iter().take(shown));
+    }
+
+    if hidden > 0 && height > 0 {
+        let ellipsis = theme.glyphs.ellipsis;
+        let summary = match done {
+            0 => format!("{ellipsis} {hidden} more"),
+            done => format!("{ellipsis} {hidden} more ({done} done)"),
+        };
+        lines.push(theme.paint(&summary, theme.colors.dim));
+    }
+    lines
+}
+
+/// the next terminal event, or never if we arent listening for them
+async fn next_event(events: &mut Option<EventStream>) -> Option<io::Result<Event>> {
+    match events {
+        Some(events) => events.next().await,
+        None => futures::future::pending().await,
+    }
+}
+
+/// how the children of a widget did
+#[derive(Debug, Default)]
+struct Tally {
+    done: usize,
+    failed: usize,
+    cancelled: usize,
+    skipped: usize,
+}
+
+impl Tally {
+    /// counts `widget`, which failed if anything under it failed too
+    fn count(&mut self, widget: &Widget, failed: bool) {
+        match widget.state() {
+            _ if widget.is_static() => {}
+            _ if failed => self.failed += 1,
+            State::Done => self.done += 1,
+            State::Cancelled => self.cancelled += 1,
+            State::Skipped => self.skipped += 1,
+            _ => {}
+        }
+    }
+}
+
+impl Display for Tally {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.done, "done"),
+            (self.failed, "failed"),
+            (self.cancelled, "cancelled"),
+            (self.skipped, "skipped"),
+        ];
+        let counts = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, what)| format!("{count} {what}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", counts.join(", "))
     }
 }
 
@@ -239,4 +1429,929 @@ struct RenderInner {
     new_index: usize,
     all_done: bool,
     active: bool,
+    failed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{strip_escapes, Buffer};
+    use crate::table::Column;
+
+    /// an app that draws into a buffer instead of all over the terminal
+    fn test_app() -> App {
+        let mut app = App::default();
+        app.set_output(Buffer::default());
+        app
+    }
+
+    /// renders until `done` says the app got where its supposed to, however long that takes
+    fn render_until(app: &mut App, done: impl Fn(&App) -> bool) {
+        let start = time::Instant::now();
+        app.render();
+        while !done(app) {
+            assert!(start.elapsed() < Duration::from_secs(10), "never got there");
+            std::thread::sleep(Duration::from_millis(1));
+            app.render();
+        }
+    }
+
+    #[test]
+    fn test_progress_updates() {
+        let mut app = test_app();
+        let progress = app.add_widget(Widget::new_progress("progress", 0, 10));
+        let percentage = app.add_widget(Widget::new_percentage("percentage", 0));
+
+        let progress_sender = app.update_sender(progress);
+        let percentage_sender = app.update_sender(percentage);
+        app.runtime.block_on(async {
+            progress_sender.set_total(20).await;
+            progress_sender.set_progress(5).await;
+            progress_sender.increment_progress(3).await;
+            percentage_sender.set_percentage(0.5).await;
+        });
+        app.render();
+
+        assert_eq!(
+            app.widget(progress).unwrap().widget,
+            WidgetType::Progress {
+                progress: 8,
+                total: 20
+            }
+        );
+        assert_eq!(
+            app.widget(percentage).unwrap().widget,
+            WidgetType::Percentage { progress: 0.5 }
+        );
+        assert!(app.widget(progress).unwrap().is_active());
+    }
+
+    #[test]
+    fn test_failed_task() {
+        let mut app = test_app();
+        let root = app.add_widget(Widget::new_task("root", 0));
+        let ok = app.add_widget(Widget::new_task("ok", 1));
+        let failed = app.add_widget(Widget::new_task("failed", 1));
+        let other = app.add_widget(Widget::new_task("other", 0));
+
+        app.add_task(|_| async {}, ok);
+        app.add_task(|_| async { Err::<(), _>("404 Not Found") }, failed);
+        app.add_task(|_| async { Ok::<_, String>(5) }, other);
+
+        assert!(app.run_until_done().is_ok());
+
+        assert_eq!(app.widget(failed).unwrap().widget, WidgetType::Error);
+        assert_eq!(app.widget(failed).unwrap().message, "failed: 404 Not Found");
+        assert!(app.widget(root).unwrap().is_done());
+        assert!(app.widget(root).unwrap().children_failed);
+        assert!(app.widget(other).unwrap().is_done());
+        assert!(!app.widget(other).unwrap().children_failed);
+    }
+
+    #[test]
+    fn test_add_child() {
+        let mut app = test_app();
+        let list = app.add_widget(Widget::new_task("list repos", 0));
+        app.add_widget(Widget::new_text("existing", 1));
+        let after = app.add_widget(Widget::new_task("after", 0));
+
+        app.add_task(
+            |s| async move {
+                for name in ["repo 1", "repo 2"] {
+                    let child = s.add_child(Widget::new_task(name, 0)).await;
+                    child.send(Update::SetDone).await;
+                }
+            },
+            list,
+        );
+        app.add_task(
+            |s| async move { s.send(Update::set_message("moved")).await },
+            after,
+        );
+
+        assert!(app.run_until_done().is_ok());
+
+        let rows = app
+            .widgets
+            .iter()
+            .map(|w| (w.message.as_str(), w.indent))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                ("list repos", 0),
+                ("existing", 1),
+                ("repo 1", 1),
+                ("repo 2", 1),
+                ("moved", 0)
+            ]
+        );
+        assert_eq!(app.widget(after).unwrap().message, "moved");
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut app = test_app();
+        let waiting = app.add_widget(Widget::new_task("waiting", 0));
+        let untouched = app.add_widget(Widget::new_progress("untouched", 0, 10));
+        let canceller = app.add_widget(Widget::new_task("canceller", 0));
+
+        app.add_task(
+            |s| async move {
+                s.cancelled().await;
+                // we should never get past cancellation
+                s.send(Update::set_message("oops")).await;
+            },
+            waiting,
+        );
+        app.add_task(|s| async move { s.cancel.cancel() }, canceller);
+
+        assert_eq!(app.run_until_done(), Err(RunError::Aborted));
+        assert_eq!(app.widget(waiting).unwrap().widget, WidgetType::Cancelled);
+        assert_eq!(app.widget(waiting).unwrap().message, "waiting");
+        assert_eq!(app.widget(untouched).unwrap().widget, WidgetType::Cancelled);
+    }
+
+    #[test]
+    fn test_retry() {
+        let mut app = test_app();
+        let flaky = app.add_widget(Widget::new_task("flaky", 0));
+        let hopeless = app.add_widget(Widget::new_task("hopeless", 0));
+
+        let options = TaskOptions::default()
+            .max_attempts(3)
+            .backoff(Duration::from_millis(1))
+            .timeout(Duration::from_millis(50));
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        app.add_task_with(
+            move |_| {
+                let attempts = attempts.clone();
+                async move {
+                    match attempts.fetch_add(1, Ordering::Relaxed) {
+                        0 => Err("502 Bad Gateway"),
+                        _ => Ok(()),
+                    }
+                }
+            },
+            flaky,
+            options.clone(),
+        );
+        app.add_task_with(
+            |_| tokio::time::sleep(Duration::from_secs(1)),
+            hopeless,
+            options,
+        );
+
+        assert!(app.run_until_done().is_ok());
+
+        assert!(app.widget(flaky).unwrap().is_done());
+        assert_eq!(app.widget(flaky).unwrap().attempts, ["502 Bad Gateway"]);
+        assert_eq!(app.widget(flaky).unwrap().status, None);
+
+        assert_eq!(app.widget(hopeless).unwrap().widget, WidgetType::Error);
+        assert_eq!(app.widget(hopeless).unwrap().attempts.len(), 3);
+        assert_eq!(
+            app.widget(hopeless).unwrap().message,
+            "hopeless: timed out after 50ms (gave up after 3 attempts)"
+        );
+    }
+
+    #[test]
+    fn test_concurrency_limit() {
+        let mut app = test_app();
+        app.set_concurrency_limit(2);
+        app.set_group_limit("github", 1);
+
+        let started = Arc::new(std::sync::Mutex::new(vec![]));
+        // holds every task up once it has started, until its opened
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        for n in 0..4 {
+            let idx = app.add_widget(Widget::new_task(format!("task {n}"), 0));
+            let started = started.clone();
+            let gate = gate.clone();
+            app.add_task_with(
+                move |_| {
+                    let (started, gate) = (started.clone(), gate.clone());
+                    async move {
+                        started.lock().unwrap().push(n);
+                        gate.acquire().await.unwrap().forget();
+                    }
+                },
+                idx,
+                TaskOptions::default().group("github"),
+            );
+        }
+
+        render_until(&mut app, |app| {
+            app.widget(0).unwrap().is_active()
+                && app.widget(1).unwrap().queued
+                && app.widget(3).unwrap().queued
+        });
+        assert!(!app.widget(1).unwrap().is_active());
+        assert_eq!(*started.lock().unwrap(), [0]);
+
+        gate.add_permits(4);
+        assert!(app.run_until_done().is_ok());
+        assert_eq!(*started.lock().unwrap(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let mut app = test_app();
+        let user = app.add_widget(Widget::new_task("resolve user", 0));
+        let repos = app.add_widget(Widget::new_task("list repos", 0));
+        let total = app.add_widget(Widget::new_task("aggregate", 0));
+        let broken = app.add_widget(Widget::new_task("broken", 0));
+        let skipped = app.add_widget(Widget::new_task("skipped", 0));
+        let downstream = app.add_widget(Widget::new_task("downstream", 0));
+
+        let user = app.add_task_after((), async |_, ()| Ok::<_, String>("octocat"), user);
+        let repos = app.add_task_after(
+            user.clone(),
+            async |_, user: &str| Ok::<_, String>(vec![format!("{user}/hello"); 3]),
+            repos,
+        );
+        let total = app.add_task_after(
+            (user, repos),
+            async |s: UpdateSender, (user, repos): (&str, Vec<String>)| {
+                s.send(Update::set_message(format!("{user}: {}", repos.len())))
+                    .await;
+            },
+            total,
+        );
+
+        let broken = app.add_task_after((), async |_, ()| Err::<(), _>("404"), broken);
+        let skipped = app.add_task_after(broken, async |_, ()| {}, skipped);
+        app.add_task_after(vec![skipped, total], async |_, _| {}, downstream);
+
+        assert!(app.run_until_done().is_ok());
+
+        assert_eq!(app.widget(2).unwrap().message, "octocat: 3");
+        assert_eq!(app.widget(3).unwrap().widget, WidgetType::Error);
+        assert_eq!(app.widget(4).unwrap().widget, WidgetType::Skipped);
+        assert_eq!(
+            app.widget(4).unwrap().message,
+            "skipped (skipped, broken failed)"
+        );
+        assert_eq!(app.widget(5).unwrap().widget, WidgetType::Skipped);
+        assert_eq!(
+            app.widget(5).unwrap().message,
+            "downstream (skipped, skipped was skipped)"
+        );
+    }
+
+    #[test]
+    fn test_skip_without_a_slot() {
+        let mut app = test_app();
+        app.set_concurrency_limit(1);
+        let broken = app.add_widget(Widget::new_task("broken", 0));
+        let busy = app.add_widget(Widget::new_task("busy", 0));
+        let skipped = app.add_widget(Widget::new_task("skipped", 0));
+
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let held = gate.clone();
+        let broken = app.add_task(async |_| Err::<(), _>("404"), broken);
+        app.add_task(async move |_| held.acquire().await.unwrap().forget(), busy);
+        app.add_task_after(broken, async |_, ()| {}, skipped);
+
+        // busy holds the only slot, but skipping doesnt need one
+        render_until(&mut app, |app| {
+            app.widget(skipped).unwrap().widget == WidgetType::Skipped
+        });
+        assert!(!app.widget(skipped).unwrap().queued);
+
+        gate.add_permits(1);
+        assert!(app.run_until_done().is_ok());
+    }
+
+    #[test]
+    fn test_task_handles() {
+        let mut app = test_app();
+        let stars = app.add_widget(Widget::new_task("stars", 0));
+        let doubled = app.add_widget(Widget::new_task("doubled", 0));
+        let broken = app.add_widget(Widget::new_task("broken", 0));
+
+        let stars = app.add_task(async |_| Ok::<_, String>(21), stars);
+        let waiting = stars.clone();
+        let doubled = app.add_task(
+            async move |_| waiting.wait().await.map(|stars| stars * 2),
+            doubled,
+        );
+        let broken = app.add_task(async |_| Err::<usize, _>("rate limited"), broken);
+
+        assert!(app.run_until_done().is_ok());
+
+        assert_eq!(stars.result(), Some(Ok(21)));
+        // taking from one handle leaves the output there for the others
+        assert_eq!(doubled.clone().take(), Some(Ok(42)));
+        assert_eq!(doubled.result(), Some(Ok(42)));
+        assert_eq!(doubled.take(), Some(Ok(42)));
+        assert_eq!(
+            broken.take(),
+            Some(Err(TaskError::Failed("rate limited".to_string())))
+        );
+    }
+
+    #[test]
+    fn test_run_inside_runtime() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+
+        runtime.block_on(async {
+            let mut app = test_app();
+            let idx = app.add_widget(Widget::new_task("inside", 0));
+            let handle = app.add_task(async |_| Ok::<_, String>("done"), idx);
+
+            assert!(app.run().await.is_ok());
+            assert_eq!(handle.take(), Some(Ok("done")));
+        });
+    }
+
+    #[test]
+    fn test_explicit_runtime() {
+        let runtime = Builder::new_multi_thread().enable_all().build().unwrap();
+        let mut app = App::new(runtime.handle().clone());
+        app.set_output(Buffer::default());
+        let idx = app.add_widget(Widget::new_task("sleepy", 0));
+        let handle = app.add_task(
+            async |_| {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                Ok::<_, String>("done")
+            },
+            idx,
+        );
+
+        assert!(app.run_until_done().is_ok());
+        assert_eq!(handle.take(), Some(Ok("done")));
+
+        // current thread runtimes only get driven from their own block_on
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let mut app = App::new(runtime.handle().clone());
+        app.set_output(Buffer::default());
+        let idx = app.add_widget(Widget::new_task("sleepy", 0));
+        app.add_task(|_| tokio::time::sleep(Duration::from_millis(10)), idx);
+        assert!(runtime.block_on(app.run()).is_ok());
+    }
+
+    #[test]
+    fn test_run_until_done_on_current_thread() {
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let mut app = App::new(runtime.handle().clone());
+        app.set_output(Buffer::default());
+        assert_eq!(app.run_until_done(), Err(RunError::CurrentThread));
+    }
+
+    #[test]
+    fn test_render_frames() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+
+        app.add_widget(Widget::new_text("stats", 0));
+        let repo = app.add_widget(Widget::new_task("fetch repos", 1));
+        let repo = app.update_sender(repo);
+        app.render();
+
+        app.runtime.block_on(async {
+            repo.send(Update::set_message("fetched")).await;
+            repo.send(Update::SetDone).await;
+        });
+        app.render();
+
+        let frames = buffer.frames();
+        assert_eq!(frames.len(), 2);
+        // the first frame is drawn where the cursor is, after that we draw over the last one
+        assert!(!frames[0].starts_with("\x1b[2A"));
+        assert!(frames[1].starts_with("\x1b[2A"));
+        assert_eq!(buffer.plain_frames()[0], "stats\n • [ ] fetch repos\n");
+        // only the line that changed gets redrawn
+        assert_eq!(buffer.last_frame().unwrap(), " • [✓] fetched in 0.0s\n");
+
+        // nothing changed, so nothing gets drawn
+        app.render();
+        assert_eq!(buffer.frames().len(), 2);
+    }
+
+    #[test]
+    fn test_plain_mode() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+
+        app.add_widget(Widget::new_text("stats", 0));
+        let repos = app.add_widget(Widget::new_task("fetch repos", 1));
+        let stars = app.add_widget(Widget::new_task("fetch stars", 1));
+        let repos = app.update_sender(repos);
+        let stars = app.update_sender(stars);
+
+        let steps = [
+            vec![],
+            vec![(&repos, Update::SetActive)],
+            vec![(&repos, Update::set_message("fetched 3 repos"))],
+            vec![(&repos, Update::SetActive)],
+            vec![
+                (&repos, Update::SetDone),
+                (&stars, Update::SetError("rate limited".to_string())),
+            ],
+        ];
+        for step in steps {
+            app.runtime.block_on(async {
+                for (sender, update) in step {
+                    sender.send(update).await;
+                }
+            });
+            app.render();
+        }
+
+        assert_eq!(
+            buffer.frames().concat(),
+            "stats\n  \
+             [started] fetch repos\n  \
+             [message] fetched 3 repos\n  \
+             [done] fetched 3 repos\n  \
+             [failed] fetch stars: rate limited\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_to_width() {
+        let buffer = Buffer::with_size(41, 10);
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+
+        app.add_widget(Widget::new_text(
+            "a message that is far too long to fit on one line",
+            0,
+        ));
+        let mut task = Widget::new_task("a task that is also way too long", 1);
+        task.set_done();
+        app.add_widget(task);
+        let mut progress = Widget::new_progress("progress", 0, 20);
+        progress.update_discrete_progress(5);
+        progress.active = false;
+        app.add_widget(progress);
+        app.render();
+
+        let frame = buffer.last_frame().unwrap();
+        let lines = frame.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "a message that is far too long to fit o…");
+        assert_eq!(lines[1], " • [✓] a task that is also way too long");
+        // a third of the width goes to the bar
+        assert_eq!(lines[2], "[ ] [━━━[ 5/20]   ] progress");
+        assert!(lines.iter().all(|line| line.chars().count() <= 40));
+
+        // shrinking the window redraws the frame over the rows it wrapped onto,
+        // leaving everything above it alone
+        buffer.set_size(21, 10);
+        app.render();
+        let frames = buffer.frames();
+        assert!(frames[1].starts_with("\x1b[6A\x1b[J"));
+        let frame = buffer.last_frame().unwrap();
+        assert_eq!(frame.lines().next(), Some("a message that is f…"));
+    }
+
+    #[test]
+    fn test_narrow_width() {
+        let buffer = Buffer::with_size(20, 10);
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+
+        app.add_widget(Widget::new_task("fetch", 0));
+        let mut progress = Widget::new_progress("progress", 1, 20);
+        progress.update_discrete_progress(5);
+        app.add_widget(progress);
+        let mut counter = Widget::new_counter("repos", 0, 300);
+        counter.count(Outcome::Failed);
+        app.add_widget(counter);
+        app.render();
+
+        let frame = buffer.last_frame().unwrap();
+        assert_eq!(frame.lines().count(), 3);
+        assert!(frame.lines().all(|line| line.chars().count() <= 19));
+        assert!(app
+            .frame
+            .iter()
+            .all(|line| strip_escapes(line).chars().count() <= 19));
+    }
+
+    #[test]
+    fn test_viewport() {
+        let buffer = Buffer::with_size(40, 6);
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+
+        for n in 0..8 {
+            let mut repo = Widget::new_task(format!("repo {n}"), 0);
+            match n {
+                2 => repo.set_error("404"),
+                5 => repo.active = true,
+                6 | 7 => {}
+                _ => repo.set_done(),
+            }
+            app.add_widget(repo);
+        }
+        app.render();
+
+        let frame = buffer.last_frame().unwrap();
+        let lines = frame.lines().collect::<Vec<_>>();
+        // the failed & running repos come first, then the ones that havent started
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].ends_with("repo 2: 404"));
+        assert!(lines[1].ends_with("repo 5"));
+        assert!(lines[2].ends_with("repo 6"));
+        assert!(lines[3].ends_with("repo 7"));
+        assert_eq!(lines[4], "… 4 more (4 done)");
+    }
+
+    #[test]
+    fn test_collapse() {
+        let buffer = Buffer::with_size(60, 20);
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_collapse(Collapse::Succeeded);
+
+        for (name, broken) in [("fetch repos", false), ("fetch stars", true)] {
+            app.add_widget(Widget::new_task(name, 0));
+            for n in 0..3 {
+                let mut repo = Widget::new_task(format!("repo {n}"), 1);
+                match n {
+                    2 if broken => repo.set_error("404"),
+                    _ => repo.set_done(),
+                }
+                app.add_widget(repo);
+            }
+        }
+        app.render();
+
+        let frame = buffer.last_frame().unwrap();
+        let lines = frame.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("[✓] fetch repos (3 done) in "));
+        assert_eq!(lines[1], "[✗] fetch stars in 0.0s");
+        assert_eq!(lines[4], " • [⚠️] repo 2: 404");
+
+        // everything gets collapsed once failures dont keep it open
+        app.set_collapse(Collapse::Done);
+        app.render();
+        let frame = buffer.last_frame().unwrap();
+        assert!(frame.contains("[✗] fetch stars (2 done, 1 failed) in "));
+        assert_eq!(app.frame.len(), 2);
+    }
+
+    #[test]
+    fn test_summary() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+        app.set_print_summary(true);
+
+        app.add_widget(Widget::new_text("stats", 0));
+        let slow = app.add_widget(Widget::new_task("slow", 0));
+        let fast = app.add_widget(Widget::new_task("fast", 0));
+        let broken = app.add_widget(Widget::new_task("broken", 0));
+        let skipped = app.add_widget(Widget::new_task("skipped", 0));
+
+        app.add_task(|_| tokio::time::sleep(Duration::from_millis(50)), slow);
+        app.add_task(|_| async {}, fast);
+        let broken = app.add_task_after((), async |_, ()| Err::<(), _>("404"), broken);
+        app.add_task_after(broken, async |_, ()| {}, skipped);
+
+        let summary = app.run_until_done().unwrap();
+        assert_eq!(
+            (summary.succeeded, summary.failed, summary.skipped),
+            (2, 1, 1)
+        );
+        assert!(!summary.is_success());
+        assert!(summary.elapsed >= Duration::from_millis(50));
+        assert_eq!(summary.slowest[0].0, "slow");
+        assert_eq!(summary.errors, ["broken: 404"]);
+
+        let printed = buffer.frames().concat();
+        assert!(printed.contains("2 succeeded, 1 failed, 1 skipped in "));
+        assert!(printed.ends_with("errors:\n  broken: 404\n"));
+    }
+
+    #[test]
+    fn test_summary_children() {
+        let mut app = test_app();
+        let list = app.add_widget(Widget::new_task("list repos", 0));
+        app.add_task(
+            |s| async move {
+                for n in 0..3 {
+                    let child = s.add_child(Widget::new_task(format!("repo {n}"), 0)).await;
+                    match n {
+                        0 => child.send(Update::SetError("404".to_string())).await,
+                        _ => child.send(Update::SetDone).await,
+                    }
+                }
+            },
+            list,
+        );
+
+        let summary = app.run_until_done().unwrap();
+        // the two repos that worked & the task that listed them
+        assert_eq!((summary.succeeded, summary.failed), (3, 1));
+        assert_eq!(summary.errors, ["repo 0: 404"]);
+        assert!(!summary.is_success());
+    }
+
+    #[test]
+    fn test_interactive() {
+        use crossterm::event::{KeyCode, KeyModifiers};
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        let buffer = Buffer::with_size(81, 20);
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_interactive(true);
+
+        app.add_widget(Widget::new_text("repos", 0));
+        let mut broken = Widget::new_task("broken", 1);
+        broken.set_error("404 Not Found");
+        app.add_widget(broken);
+        app.add_widget(Widget::new_task("waiting", 1));
+        app.render();
+
+        // the cursor starts on the first widget
+        assert_eq!(app.handle_key(key(KeyCode::Down)), Action::Redraw);
+        app.render();
+        let frame = buffer.plain_frames().concat();
+        assert!(frame.contains("›  • [⚠️] broken: 404 Not Found"));
+
+        // the details go next to the tree
+        app.handle_key(key(KeyCode::Enter));
+        app.render();
+        let lines = app
+            .frame
+            .iter()
+            .map(|line| strip_escapes(line))
+            .collect::<Vec<_>>();
+        assert!(lines[0].ends_with("│ broken: 404 Not Found"));
+        assert!(lines[2].ends_with("│ state: failed"));
+        // the separator comes from the theme like everything else
+        app.set_theme(Theme::ASCII);
+        app.render();
+        assert!(strip_escapes(&app.frame[2]).ends_with("| state: failed"));
+
+        // folding the parent hides the children & moves the cursor up to it
+        app.handle_key(key(KeyCode::Esc));
+        app.handle_key(key(KeyCode::Left));
+        app.handle_key(key(KeyCode::Left));
+        app.render();
+        assert_eq!(app.frame.len(), 1);
+        app.handle_key(key(KeyCode::Right));
+        app.render();
+        assert_eq!(app.frame.len(), 3);
+
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(app.handle_key(ctrl_c), Action::Cancel);
+    }
+
+    #[test]
+    fn test_logs() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+
+        let fetch = app.add_widget(Widget::new_task("fetch", 0));
+        app.add_task(
+            async |s: UpdateSender| {
+                s.log("page 1").await;
+                // anything running inside of the task can find its way back to the widget
+                UpdateSender::current().unwrap().try_log("page 2\npage 3");
+                Err::<(), _>("rate limited")
+            },
+            fetch,
+        );
+        assert!(UpdateSender::current().is_none());
+
+        let summary = app.run_until_done().unwrap();
+        assert_eq!(
+            app.widget(fetch).unwrap().logs,
+            ["page 1", "page 2", "page 3"]
+        );
+        assert_eq!(
+            summary.logs,
+            [(
+                "fetch: rate limited".to_string(),
+                vec![
+                    "page 1".to_string(),
+                    "page 2".to_string(),
+                    "page 3".to_string()
+                ]
+            )]
+        );
+        assert!(buffer.frames().concat().contains("[log] fetch: page 3\n"));
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut app = test_app();
+        let download = app.add_widget(Widget::new_bytes("download", 0, None));
+        let sender = app.update_sender(download);
+
+        app.runtime.block_on(async {
+            sender.set_bytes_total(Some(3 * 1024 * 1024)).await;
+            let chunks = vec![Ok::<_, ()>(vec![0u8; 1024 * 1024]); 2];
+            let body = sender.track_bytes(futures::stream::iter(chunks));
+            assert_eq!(body.collect::<Vec<_>>().await.len(), 2);
+        });
+        app.render();
+
+        let widget = app.widget(download).unwrap();
+        assert_eq!(
+            widget.widget,
+            WidgetType::Bytes {
+                transferred: 2 * 1024 * 1024,
+                total: Some(3 * 1024 * 1024)
+            }
+        );
+        assert!(!widget.is_done());
+    }
+
+    #[test]
+    fn test_bytes_cut_short() {
+        let mut app = test_app();
+        let download = app.add_widget(Widget::new_bytes("download", 0, Some(100)));
+        app.add_task(async |s| s.add_bytes(40).await, download);
+
+        // the body ended early, but the task finished so the widget is done too
+        assert!(app.run_until_done().is_ok());
+        let widget = app.widget(download).unwrap();
+        assert_eq!(
+            widget.widget,
+            WidgetType::Bytes {
+                transferred: 40,
+                total: Some(100)
+            }
+        );
+        assert!(widget.is_done());
+    }
+
+    #[test]
+    fn test_indeterminate() {
+        let mut app = test_app();
+        let pages = app.add_widget(Widget::new_indeterminate("pages", 0));
+        let sender = app.update_sender(pages);
+
+        app.runtime.block_on(sender.increment_progress(2));
+        app.render();
+        assert_eq!(
+            app.widget(pages).unwrap().widget,
+            WidgetType::Indeterminate { progress: 2 }
+        );
+
+        app.runtime.block_on(async {
+            sender.set_total(10).await;
+            sender.increment_progress(1).await;
+        });
+        app.render();
+        assert_eq!(
+            app.widget(pages).unwrap().widget,
+            WidgetType::Progress {
+                progress: 3,
+                total: 10
+            }
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+        let columns = vec![Column::left("repo"), Column::right("stars")];
+        let stats = app.add_widget(Widget::new_table("stats", 0, columns));
+        let sender = app.update_sender(stats);
+
+        app.runtime.block_on(async {
+            sender.add_row(["tokio", "24301"]).await;
+            sender.add_row(["crossterm", "2873"]).await;
+        });
+        app.render();
+        app.runtime.block_on(sender.set_row(["crossterm", "2874"]));
+        app.render();
+
+        // only the row that changed gets printed again
+        assert_eq!(
+            buffer.frames().concat(),
+            "stats\nrepo  stars\ntokio  24301\ncrossterm  2873\ncrossterm  2874\n"
+        );
+    }
+
+    #[test]
+    fn test_charts() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+        let commits = app.add_widget(Widget::new_sparkline("commits", 0));
+        let languages = app.add_widget(Widget::new_histogram("languages", 0));
+        let commits = app.update_sender(commits);
+        let languages = app.update_sender(languages);
+
+        app.runtime.block_on(async {
+            commits.set_values(vec![0.0, 1.0, 2.0]).await;
+            commits.push_value(7.0).await;
+            languages.set_bar("rust", 2.0).await;
+            languages.set_bar("python", 1.0).await;
+            languages.set_bar("rust", 4.0).await;
+        });
+        app.render();
+        // nothing changed, so nothing gets printed again
+        app.render();
+
+        assert_eq!(
+            buffer.frames().concat(),
+            format!(
+                "[▁▂▃█{:21}] commits\nlanguages\nrust   {:<25} 4\npython {:<25} 1\n",
+                "",
+                "━".repeat(25),
+                "━━━━━━╸"
+            )
+        );
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut app = test_app();
+        let repos = app.add_widget(Widget::new_counter("repos", 0, 10));
+        let sender = app.update_sender(repos);
+
+        app.runtime.block_on(async {
+            sender.count(Outcome::Succeeded).await;
+            sender.count(Outcome::Succeeded).await;
+        });
+        app.render();
+        assert!(!app.widget(repos).unwrap().is_done());
+
+        app.runtime.block_on(async {
+            sender.set_total(4).await;
+            sender.count(Outcome::Failed).await;
+            sender.count(Outcome::Skipped).await;
+        });
+        app.render();
+        let widget = app.widget(repos).unwrap();
+        assert_eq!(
+            widget.widget,
+            WidgetType::Counter {
+                succeeded: 2,
+                failed: 1,
+                skipped: 1,
+                total: 4
+            }
+        );
+        assert!(widget.is_done());
+    }
+
+    #[test]
+    fn test_retry_output() {
+        let buffer = Buffer::default();
+        let mut app = test_app();
+        app.set_output(buffer.clone());
+        app.set_render_mode(RenderMode::Plain);
+        app.set_print_summary(true);
+        let flaky = app.add_widget(Widget::new_task("flaky", 0));
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let options = TaskOptions::default()
+            .max_attempts(3)
+            .backoff(Duration::from_millis(1));
+        app.add_task_with(
+            move |_| {
+                let attempts = attempts.clone();
+                async move {
+                    match attempts.fetch_add(1, Ordering::Relaxed) {
+                        0 => Err("502 Bad Gateway"),
+                        1 => Err("timed out"),
+                        _ => Ok(()),
+                    }
+                }
+            },
+            flaky,
+            options,
+        );
+
+        let summary = app.run_until_done().unwrap();
+        assert_eq!(
+            summary.attempts,
+            [(
+                "flaky".to_string(),
+                vec!["502 Bad Gateway".to_string(), "timed out".to_string()]
+            )]
+        );
+
+        let printed = buffer.frames().concat();
+        let retried = printed.find("[retry] flaky: 502 Bad Gateway\n").unwrap();
+        let done = printed.find("[done] flaky\n").unwrap();
+        assert!(retried < done);
+        assert!(printed.contains("[retry] flaky: timed out\n"));
+        assert!(printed.ends_with(
+            "attempts:\n  flaky\n    attempt 1: 502 Bad Gateway\n    attempt 2: timed out\n"
+        ));
+    }
 }
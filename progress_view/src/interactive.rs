@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+use std::time;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+
+use crate::widget::{format_duration, Widget};
+
+/// what the app should do after a key was pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    Nothing,
+    /// something about what is on screen changed
+    Redraw,
+    /// Ctrl-C, raw mode swallows the signal so it comes in as a key instead
+    Cancel,
+    Quit,
+}
+
+/// moves a cursor over the widgets with the keyboard, see `App::set_interactive`
+///
+/// * up & down (or k & j) move the cursor
+/// * right (or l) expands the selected subtree, left (or h) collapses it
+///   or moves to the parent if its already collapsed
+/// * enter shows the details of the selected widget next to the tree, esc hides them
+/// * q quits once everything is done, and cancels the run before that
+#[derive(Debug, Default)]
+pub(crate) struct Navigator {
+    /// the id of the widget the cursor is on
+    pub selected: Option<usize>,
+    /// the ids of widgets whose children have been hidden
+    pub folded: HashSet<usize>,
+    /// the ids of widgets whose children stay shown even though theyd be collapsed
+    pub expanded: HashSet<usize>,
+    /// whether the details of the selected widget are shown next to the tree
+    pub details: bool,
+    /// the ids of the widgets in the tree last frame, top to bottom
+    pub visible: Vec<usize>,
+}
+
+impl Navigator {
+    /// whether the children of `widget` with the id `id` are hidden
+    pub fn hides_children(&self, id: usize, widget: &Widget) -> bool {
+        match widget.summary {
+            Some(_) => !self.expanded.contains(&id),
+            None => self.folded.contains(&id),
+        }
+    }
+
+    /// moves the cursor around the tree of `widgets`, whose ids are `ids`
+    pub fn handle_key(&mut self, key: KeyEvent, widgets: &[Widget], ids: &[usize]) -> Action {
+        use KeyCode::*;
+
+        if key.kind == KeyEventKind::Release {
+            return Action::Nothing;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == Char('c') {
+            return Action::Cancel;
+        }
+
+        // the cursor starts on the first widget
+        let selected = self.selected.or_else(|| self.visible.first().copied());
+        let Some(selected) = selected else {
+            return match key.code {
+                Char('q') => Action::Quit,
+                _ => Action::Nothing,
+            };
+        };
+        let cursor = self.visible.iter().position(|&id| id == selected);
+        let Some(position) = ids.iter().position(|&id| id == selected) else {
+            return Action::Nothing;
+        };
+        let widget = &widgets[position];
+        let has_children = widgets
+            .get(position + 1)
+            .is_some_and(|child| child.indent > widget.indent);
+
+        match key.code {
+            Up | Char('k') => {
+                let cursor = cursor.map_or(0, |cursor| cursor.saturating_sub(1));
+                self.selected = self.visible.get(cursor).copied();
+            }
+            Down | Char('j') => {
+                let cursor = cursor.map_or(0, |cursor| cursor + 1);
+                let cursor = cursor.min(self.visible.len().saturating_sub(1));
+                self.selected = self.visible.get(cursor).copied();
+            }
+            Right | Char('l') if has_children => {
+                self.folded.remove(&selected);
+                self.expanded.insert(selected);
+                self.selected = Some(selected);
+            }
+            Left | Char('h') if has_children && !self.hides_children(selected, widget) => {
+                self.expanded.remove(&selected);
+                self.folded.insert(selected);
+                self.selected = Some(selected);
+            }
+            // the parent is the closest widget above that is indented less
+            Left | Char('h') => {
+                let parent = widgets[..position]
+                    .iter()
+                    .rposition(|parent| parent.indent < widget.indent);
+                self.selected = Some(parent.map_or(selected, |parent| ids[parent]));
+            }
+            Enter => {
+                self.selected = Some(selected);
+                self.details = !self.details;
+            }
+            Esc => self.details = false,
+            Char('q') => return Action::Quit,
+            _ => return Action::Nothing,
+        }
+        Action::Redraw
+    }
+}
+
+/// everything there is to know about `widget`, log included, wrapped to fit in `width` columns
+pub(crate) fn details(widget: &Widget, width: usize, now: time::Instant) -> Vec<String> {
+    let state = format!("{:?}", widget.state()).to_lowercase();
+    let mut lines = vec![format!("state: {state}")];
+    if let Some(elapsed) = widget.elapsed(now) {
+        lines.push(format!("elapsed: {}", format_duration(elapsed)));
+    }
+    if let Some(status) = &widget.status {
+        lines.push(format!("status: {status}"));
+    }
+    lines.extend(
+        widget
+            .attempts
+            .iter()
+            .enumerate()
+            .map(|(n, error)| format!("attempt {}: {error}", n + 1)),
+    );
+    if !widget.logs.is_empty() {
+        lines.push("logs:".to_string());
+        lines.extend(widget.logs.iter().map(|line| format!("  {line}")));
+    }
+
+    let mut wrapped = wrap(&widget.message, width);
+    wrapped.push(String::new());
+    for line in lines {
+        wrapped.extend(wrap(&line, width));
+    }
+    wrapped
+}
+
+/// splits `text` into lines that are at most `width` characters long
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    match chars.is_empty() {
+        true => vec![String::new()],
+        false => chars
+            .chunks(width.max(1))
+            .map(|chunk| chunk.iter().collect())
+            .collect(),
+    }
+}
+
+/// puts the terminal into raw mode, and back out of it when dropped
+#[derive(Debug)]
+pub(crate) struct RawMode;
+
+impl RawMode {
+    pub fn enable() -> Option<Self> {
+        terminal::enable_raw_mode().ok().map(|_| RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        terminal::disable_raw_mode().ok();
+    }
+}
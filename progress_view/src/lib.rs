@@ -5,6 +5,14 @@
 #![feature(decl_macro)]
 
 pub mod app;
+mod interactive;
+pub mod output;
+pub mod summary;
+pub mod table;
+pub mod task;
+pub mod theme;
 pub mod update;
 pub mod widget;
-pub mod macros;
\ No newline at end of file
+pub mod macros;
+#[cfg(feature = "tracing-layer")]
+pub mod log;
\ No newline at end of file
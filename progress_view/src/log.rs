@@ -0,0 +1,116 @@
+use std::fmt::{self, Write};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::app::UpdateSender;
+
+/// a `tracing_subscriber` layer that sends every event emitted inside of a task
+/// to the log of that tasks widget, instead of to stderr where it would end up
+/// all over the live display
+///
+/// events from outside of tasks are left to the other layers, keep those from
+/// printing the rest with `in_task`
+///
+/// events dont wait for the app to catch up, so if the update channel is full
+/// (it holds 64 updates) the lines get dropped instead of blocking the task
+///
+/// ```ignore
+/// tracing_subscriber::registry()
+///     .with(WidgetLogs)
+///     .with(fmt::layer().with_filter(filter_fn(|_| !log::in_task())))
+///     .init();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WidgetLogs;
+
+impl<S: Subscriber> Layer<S> for WidgetLogs {
+    fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+        let Some(sender) = UpdateSender::current() else {
+            return;
+        };
+
+        let mut line = Line::default();
+        event.record(&mut line);
+        let line = format!("{}{}", line.message, line.fields);
+        let level = *event.metadata().level();
+        let line = match level == Level::INFO {
+            true => line,
+            false => format!("{}: {line}", level.to_string().to_lowercase()),
+        };
+        sender.try_log(line);
+    }
+}
+
+/// whether this is being called from inside of a task, where `WidgetLogs` picks up events
+pub fn in_task() -> bool {
+    UpdateSender::current().is_some()
+}
+
+/// the message of an event, followed by the rest of its fields
+///
+/// `fetched page page=3 repos=100`
+#[derive(Debug, Default)]
+struct Line {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Line {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let written = match field.name() {
+            "message" => write!(self.message, "{value:?}"),
+            name => write!(self.fields, " {name}={value:?}"),
+        };
+        written.expect("no fmt err");
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message.push_str(value),
+            _ => self.record_debug(field, &value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+    use crate::output::Buffer;
+    use crate::widget::Widget;
+    use tokio::runtime::Builder;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_widget_logs() {
+        // the subscriber is only the default on this thread, so the tasks have to run on it too
+        let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+        let subscriber = tracing_subscriber::registry().with(WidgetLogs);
+
+        tracing::subscriber::with_default(subscriber, || {
+            runtime.block_on(async {
+                let mut app = App::default();
+                app.set_output(Buffer::default());
+                let fetch = app.add_widget(Widget::new_task("fetch", 0));
+                app.add_task(
+                    async |_| {
+                        assert!(in_task());
+                        tracing::info!(page = 3, "fetched page");
+                        tracing::warn!("rate limited");
+                    },
+                    fetch,
+                );
+
+                assert!(!in_task());
+                tracing::info!("nobody hears this");
+                assert!(app.run().await.is_ok());
+                assert_eq!(
+                    app.widget(fetch).unwrap().logs,
+                    ["fetched page page=3", "warn: rate limited"]
+                );
+            })
+        });
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt::{self, Debug};
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+
+use crossterm::{terminal, Command};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// somewhere for the app to draw its frames to
+///
+/// every frame gets flushed once it has been written
+pub trait Output: Write + Send + Debug {
+    /// whether this is a terminal that can handle redrawing frames,
+    /// if it isnt the app falls back to printing plain lines
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
+    /// how many columns & rows there are to draw in, `None` if there is no limit
+    fn size(&self) -> Option<(u16, u16)> {
+        None
+    }
+
+    /// whether frames should be wrapped in `BeginSynchronizedUpdate` & `EndSynchronizedUpdate`
+    fn synchronized_updates(&self) -> bool {
+        false
+    }
+}
+
+/// tells the terminal to hold off on drawing anything until `EndSynchronizedUpdate`,
+/// so it never shows a half drawn frame
+///
+/// terminals that dont support it just ignore it
+#[derive(Debug, Clone, Copy)]
+pub struct BeginSynchronizedUpdate;
+
+impl Command for BeginSynchronizedUpdate {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[?2026h")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        Ok(())
+    }
+}
+
+/// draws everything since `BeginSynchronizedUpdate` in one go
+#[derive(Debug, Clone, Copy)]
+pub struct EndSynchronizedUpdate;
+
+impl Command for EndSynchronizedUpdate {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[?2026l")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> crossterm::Result<()> {
+        Ok(())
+    }
+}
+
+/// draws to stdout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stdout;
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl Output for Stdout {
+    fn is_terminal(&self) -> bool {
+        io::stdout().is_terminal()
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        self.is_terminal().then(terminal::size)?.ok()
+    }
+
+    fn synchronized_updates(&self) -> bool {
+        self.is_terminal()
+    }
+}
+
+/// draws to stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stderr;
+
+impl Write for Stderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+impl Output for Stderr {
+    fn is_terminal(&self) -> bool {
+        io::stderr().is_terminal()
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        self.is_terminal().then(terminal::size)?.ok()
+    }
+
+    fn synchronized_updates(&self) -> bool {
+        self.is_terminal()
+    }
+}
+
+/// keeps every frame in memory, so they can be looked at later
+///
+/// clones share the same frames, so you can keep one around after
+/// giving the app another
+#[derive(Debug, Clone, Default)]
+pub struct Buffer {
+    inner: Arc<Mutex<BufferInner>>,
+}
+
+#[derive(Debug, Default)]
+struct BufferInner {
+    /// whats been written since the last flush
+    pending: Vec<u8>,
+    frames: Vec<String>,
+    size: Option<(u16, u16)>,
+}
+
+impl Buffer {
+    /// a buffer that acts like a terminal with `columns` & `rows`
+    pub fn with_size(columns: u16, rows: u16) -> Self {
+        let buffer = Self::default();
+        buffer.set_size(columns, rows);
+        buffer
+    }
+
+    /// changes the size of the pretend terminal, like resizing a window
+    pub fn set_size(&self, columns: u16, rows: u16) {
+        self.inner.lock().unwrap().size = Some((columns, rows));
+    }
+
+    /// every frame that has been drawn so far, escape codes and all
+    pub fn frames(&self) -> Vec<String> {
+        self.inner.lock().unwrap().frames.clone()
+    }
+
+    /// every frame that has been drawn so far, without any escape codes
+    pub fn plain_frames(&self) -> Vec<String> {
+        self.frames()
+            .iter()
+            .map(|frame| strip_escapes(frame))
+            .collect()
+    }
+
+    /// the last frame that was drawn, without any escape codes
+    pub fn last_frame(&self) -> Option<String> {
+        self.inner
+            .lock()
+            .unwrap()
+            .frames
+            .last()
+            .map(|frame| strip_escapes(frame))
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.inner.lock().unwrap().pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.pending.is_empty() {
+            let frame = String::from_utf8_lossy(&inner.pending).into_owned();
+            inner.frames.push(frame);
+            inner.pending.clear();
+        }
+        Ok(())
+    }
+}
+
+impl Output for Buffer {
+    /// pretends to be one, so it gets the same frames a terminal would
+    fn is_terminal(&self) -> bool {
+        true
+    }
+
+    fn size(&self) -> Option<(u16, u16)> {
+        self.inner.lock().unwrap().size
+    }
+}
+
+/// removes the ansi escape codes (colors, cursor movement, ...) from `text`
+pub fn strip_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        // CSI sequences look like `ESC [ params final` where the final byte is in @..=~
+        if chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// how many columns `text` takes up in a terminal, not counting escape codes
+pub fn display_width(text: &str) -> usize {
+    strip_escapes(text).width()
+}
+
+/// cuts `text` down to `width` columns, ending it with `ellipsis` if anything was cut off,
+/// without counting escape codes towards the width or cutting through them
+pub fn ellipsize_escaped(text: &str, width: usize, ellipsis: &str) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+
+    let room = width.saturating_sub(ellipsis.width());
+    let mut out = String::with_capacity(text.len());
+    let mut visible = 0;
+    let mut escaped = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            escaped = true;
+            out.push(c);
+            out.extend(chars.next());
+            for c in chars.by_ref() {
+                out.push(c);
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+            continue;
+        }
+        let c_width = c.width().unwrap_or_default();
+        if visible + c_width > room {
+            break;
+        }
+        out.push(c);
+        visible += c_width;
+    }
+    for c in ellipsis.chars() {
+        let c_width = c.width().unwrap_or_default();
+        if visible + c_width > width {
+            break;
+        }
+        out.push(c);
+        visible += c_width;
+    }
+    // whatever color got cut off halfway would bleed into the rest of the line otherwise
+    if escaped {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
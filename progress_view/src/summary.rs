@@ -0,0 +1,99 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::widget::format_duration;
+
+/// how a run went, returned by `App::run` once everything is done
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    pub succeeded: usize,
+    pub failed: usize,
+    pub cancelled: usize,
+    pub skipped: usize,
+    /// from the first task starting to the last one finishing
+    pub elapsed: Duration,
+    /// the tasks that took the longest & how long they took, slowest first
+    pub slowest: Vec<(String, Duration)>,
+    /// the full message of every widget that errored
+    pub errors: Vec<String>,
+    /// the message & failed attempts of every widget that had to be retried
+    pub attempts: Vec<(String, Vec<String>)>,
+    /// the message & log of every widget that logged anything
+    pub logs: Vec<(String, Vec<String>)>,
+}
+
+impl Summary {
+    /// how many of the slowest tasks get kept
+    pub const SLOWEST: usize = 5;
+
+    /// whether nothing failed or got cancelled
+    pub fn is_success(&self) -> bool {
+        self.failed == 0 && self.cancelled == 0
+    }
+}
+
+impl Display for Summary {
+    /// 12 succeeded, 2 failed in 14.2s
+    /// slowest:
+    ///   fetch repos  10.3s
+    /// errors:
+    ///   fetch stars: 404 Not Found
+    /// attempts:
+    ///   fetch repos
+    ///     attempt 1: 502 Bad Gateway
+    /// logs:
+    ///   fetch stars
+    ///     warn: rate limited
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts = [
+            (self.succeeded, "succeeded"),
+            (self.failed, "failed"),
+            (self.cancelled, "cancelled"),
+            (self.skipped, "skipped"),
+        ];
+        let counts = counts
+            .iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, what)| format!("{count} {what}"))
+            .collect::<Vec<_>>();
+        match counts.is_empty() {
+            true => write!(f, "nothing ran")?,
+            false => write!(f, "{}", counts.join(", "))?,
+        }
+        writeln!(f, " in {}", format_duration(self.elapsed))?;
+
+        if !self.slowest.is_empty() {
+            writeln!(f, "slowest:")?;
+            let width = self.slowest.iter().map(|(name, _)| name.chars().count());
+            let width = width.max().unwrap_or_default();
+            for (name, elapsed) in &self.slowest {
+                writeln!(f, "  {name:width$}  {}", format_duration(*elapsed))?;
+            }
+        }
+        if !self.errors.is_empty() {
+            writeln!(f, "errors:")?;
+            for error in &self.errors {
+                writeln!(f, "  {error}")?;
+            }
+        }
+        if !self.attempts.is_empty() {
+            writeln!(f, "attempts:")?;
+            for (name, attempts) in &self.attempts {
+                writeln!(f, "  {name}")?;
+                for (n, error) in attempts.iter().enumerate() {
+                    writeln!(f, "    attempt {}: {error}", n + 1)?;
+                }
+            }
+        }
+        if !self.logs.is_empty() {
+            writeln!(f, "logs:")?;
+            for (name, logs) in &self.logs {
+                writeln!(f, "  {name}")?;
+                for line in logs {
+                    writeln!(f, "    {line}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
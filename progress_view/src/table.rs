@@ -0,0 +1,194 @@
+use crate::widget::ellipsize;
+
+/// how many spaces go between columns
+const GAP: usize = 2;
+
+/// which side of a column its cells line up against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    /// for numbers
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Column {
+    pub header: String,
+    pub align: Align,
+}
+
+impl Column {
+    /// a column whose cells line up on the left
+    pub fn left<T: Into<String>>(header: T) -> Self {
+        Self {
+            header: header.into(),
+            align: Align::Left,
+        }
+    }
+
+    /// a column whose cells line up on the right
+    pub fn right<T: Into<String>>(header: T) -> Self {
+        Self {
+            header: header.into(),
+            align: Align::Right,
+        }
+    }
+}
+
+/// rows of cells under a line of headers, see `Widget::new_table`
+///
+/// ```text
+/// repo       stars  language
+/// tokio      24301  Rust
+/// crossterm   2874  Rust
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Table {
+    pub columns: Vec<Column>,
+    /// missing cells are left empty, cells without a column are left out
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self {
+            columns,
+            rows: vec![],
+        }
+    }
+
+    /// adds `row` to the bottom of the table
+    pub fn add_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// replaces the row that starts with the same cell as `row`, or adds it if there isnt one
+    pub fn set_row(&mut self, row: Vec<String>) {
+        let existing = self
+            .rows
+            .iter_mut()
+            .find(|existing| existing.first() == row.first());
+        match existing {
+            Some(existing) => *existing = row,
+            None => self.add_row(row),
+        }
+    }
+
+    /// how wide each column is, shrinking the widest ones until the table fits in `width`
+    fn widths(&self, width: Option<usize>) -> Vec<usize> {
+        let mut widths = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(n, column)| {
+                let cells = self.rows.iter().filter_map(|row| row.get(n));
+                let cells = cells.map(|cell| cell.chars().count());
+                cells.fold(column.header.chars().count(), usize::max)
+            })
+            .collect::<Vec<_>>();
+
+        let Some(width) = width else {
+            return widths;
+        };
+        let gaps = GAP * widths.len().saturating_sub(1);
+        let mut total = widths.iter().sum::<usize>() + gaps;
+        while total > width {
+            let Some(widest) = widths.iter_mut().filter(|width| **width > 1).max() else {
+                break;
+            };
+            *widest -= 1;
+            total -= 1;
+        }
+        widths
+    }
+
+    /// the header & then every row, each cut down to fit in `width` columns
+    pub fn render(&self, width: Option<usize>, ellipsis: &str) -> Vec<String> {
+        let widths = self.widths(width);
+        let line = |cells: Vec<&str>| {
+            let cells =
+                self.columns
+                    .iter()
+                    .zip(&widths)
+                    .enumerate()
+                    .map(|(n, (column, &width))| {
+                        let cell = ellipsize(
+                            cells.get(n).copied().unwrap_or_default(),
+                            Some(width),
+                            ellipsis,
+                        );
+                        match column.align {
+                            Align::Left => format!("{cell:<width$}"),
+                            Align::Right => format!("{cell:>width$}"),
+                        }
+                    });
+            let line = cells.collect::<Vec<_>>().join(&" ".repeat(GAP));
+            // it might still be too wide if there are too many columns to shrink
+            ellipsize(line.trim_end(), width, ellipsis)
+        };
+
+        let header = line(
+            self.columns
+                .iter()
+                .map(|column| column.header.as_str())
+                .collect(),
+        );
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| line(row.iter().map(String::as_str).collect()));
+        [header].into_iter().chain(rows).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        let mut table = Table::new(vec![
+            Column::left("repo"),
+            Column::right("stars"),
+            Column::left("language"),
+        ]);
+        table.add_row(vec!["tokio".into(), "24301".into(), "Rust".into()]);
+        table.add_row(vec!["crossterm".into(), "2874".into()]);
+        table
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            table().render(None, "…"),
+            [
+                "repo       stars  language",
+                "tokio      24301  Rust",
+                "crossterm   2874",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_row() {
+        let mut table = table();
+        table.set_row(vec!["crossterm".into(), "2875".into(), "Rust".into()]);
+        table.set_row(vec!["serde".into(), "8021".into(), "Rust".into()]);
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.rows[1], ["crossterm", "2875", "Rust"]);
+    }
+
+    #[test]
+    fn test_fit_to_width() {
+        let lines = table().render(Some(20), "…");
+        assert_eq!(
+            lines,
+            [
+                "repo    stars  lang…",
+                "tokio   24301  Rust",
+                "cross…   2874"
+            ]
+        );
+        assert!(lines.iter().all(|line| line.chars().count() <= 20));
+    }
+}
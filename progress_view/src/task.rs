@@ -0,0 +1,470 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::Future;
+use tokio::sync::{oneshot, watch};
+use tokio::time::{sleep, timeout};
+
+use crate::app::{TaskOutput, UpdateSender};
+use crate::update::Update;
+
+/// options for how a task gets run, see `App::add_task_with`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskOptions {
+    /// how long a single attempt can take before it counts as failed
+    pub timeout: Option<Duration>,
+    /// how many times the task gets run before giving up
+    pub max_attempts: usize,
+    /// how long to wait after the first failed attempt, doubles every attempt after that
+    pub backoff: Duration,
+    /// the longest we will ever wait between attempts
+    pub max_backoff: Duration,
+    /// whether to randomize the wait so retries dont all happen at once
+    pub jitter: bool,
+    /// the group this task counts towards, see `App::set_group_limit`
+    pub group: Option<String>,
+}
+
+impl Default for TaskOptions {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            max_attempts: 1,
+            backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            jitter: true,
+            group: None,
+        }
+    }
+}
+
+impl TaskOptions {
+    /// sets how long a single attempt can take
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// sets how many times the task gets run before giving up
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// sets how long to wait after the first failed attempt
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// sets the longest we will ever wait between attempts
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// sets whether to randomize the wait between attempts
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// sets the group this task counts towards
+    pub fn group<T: Into<String>>(mut self, group: T) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// how long to wait before the attempt after `attempt`
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31) as u32;
+        let delay = self
+            .backoff
+            .saturating_mul(2u32.pow(exponent))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            // wait somewhere between half & all of the delay
+            delay.mul_f64(0.5 + fastrand::f64() * 0.5)
+        } else {
+            delay
+        }
+    }
+
+    /// runs the task until it succeeds or we run out of attempts
+    ///
+    /// keeps the status of the widget up to date while waiting to retry,
+    /// and records every failed attempt on it
+    pub(crate) async fn run<E, F>(
+        &self,
+        f: E,
+        sender: UpdateSender,
+    ) -> Result<<F::Output as TaskOutput>::Output, String>
+    where
+        E: Fn(UpdateSender) -> F,
+        F: Future,
+        F::Output: TaskOutput,
+    {
+        let mut attempt = 1;
+        loop {
+            let result = match self.timeout {
+                Some(duration) => match timeout(duration, f(sender.clone())).await {
+                    Ok(output) => output.into_result(),
+                    Err(_) => Err(format!("timed out after {duration:?}")),
+                },
+                None => f(sender.clone()).await.into_result(),
+            };
+
+            let error = match result {
+                Ok(output) => {
+                    sender.send(Update::SetStatus(None)).await;
+                    return Ok(output);
+                }
+                Err(error) => error,
+            };
+            if self.max_attempts > 1 {
+                sender.send(Update::AddAttempt(error.clone())).await;
+            }
+
+            if attempt >= self.max_attempts {
+                sender.send(Update::SetStatus(None)).await;
+                return Err(match attempt {
+                    1 => error,
+                    _ => format!("{error} (gave up after {attempt} attempts)"),
+                });
+            }
+
+            // count down to the next attempt
+            attempt += 1;
+            let mut remaining = self.delay(attempt - 1);
+            while !remaining.is_zero() {
+                let status = format!(
+                    "retry {attempt}/{max} in {secs}s",
+                    max = self.max_attempts,
+                    secs = remaining.as_secs_f32().ceil()
+                );
+                sender.send(Update::SetStatus(Some(status))).await;
+
+                let step = remaining.min(Duration::from_secs(1));
+                sleep(step).await;
+                remaining -= step;
+            }
+            sender
+                .send(Update::SetStatus(Some(format!(
+                    "attempt {attempt}/{max}",
+                    max = self.max_attempts
+                ))))
+                .await;
+        }
+    }
+}
+
+/// limits how many tasks can hold a slot at once
+///
+/// tasks that cant get a slot right away wait in a queue, and get them in the
+/// order they called `Limiter::acquire`
+#[derive(Debug, Clone)]
+pub struct Limiter {
+    state: Arc<Mutex<LimiterState>>,
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    limit: usize,
+    taken: usize,
+    queue: VecDeque<oneshot::Sender<Slot>>,
+}
+
+/// a place in a `Limiter`, given back when dropped
+#[derive(Debug)]
+pub struct Slot {
+    limiter: Option<Limiter>,
+}
+
+/// a slot that has either been taken or is waiting in the queue
+#[derive(Debug)]
+pub enum Acquire {
+    Ready(Slot),
+    Queued(oneshot::Receiver<Slot>),
+}
+
+impl Limiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(LimiterState {
+                limit: limit.max(1),
+                taken: 0,
+                queue: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// takes a slot if there is one free, otherwise joins the back of the queue
+    pub fn acquire(&self) -> Acquire {
+        let mut state = self.state.lock().unwrap();
+        if state.taken < state.limit {
+            state.taken += 1;
+            Acquire::Ready(Slot {
+                limiter: Some(self.clone()),
+            })
+        } else {
+            let (sender, reciever) = oneshot::channel();
+            state.queue.push_back(sender);
+            Acquire::Queued(reciever)
+        }
+    }
+
+    /// hands a slot over to the front of the queue, or frees it if nobody is waiting
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiting) = state.queue.pop_front() {
+            let slot = Slot {
+                limiter: Some(self.clone()),
+            };
+            match waiting.send(slot) {
+                Ok(()) => return,
+                // they stopped waiting, so we dont want the slot to release itself again
+                Err(mut slot) => {
+                    slot.limiter = None;
+                }
+            }
+        }
+        state.taken -= 1;
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release();
+        }
+    }
+}
+
+impl Acquire {
+    pub fn is_queued(&self) -> bool {
+        matches!(self, Acquire::Queued(_))
+    }
+
+    /// waits until we get the slot
+    pub async fn wait(self) -> Slot {
+        match self {
+            Acquire::Ready(slot) => slot,
+            Acquire::Queued(reciever) => reciever.await.expect("limiter is alive"),
+        }
+    }
+}
+
+/// why a task didnt produce an output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// the task returned an error
+    Failed(String),
+    /// the run was cancelled before the task finished
+    Cancelled,
+    /// the task never ran because something it depends on didnt succeed
+    Skipped(String),
+}
+
+impl Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskError::Failed(error) => write!(f, "{error}"),
+            TaskError::Cancelled => write!(f, "cancelled"),
+            TaskError::Skipped(reason) => write!(f, "skipped, {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// a handle to the output of a task, which other tasks can depend on
+#[derive(Debug)]
+pub struct TaskHandle<T> {
+    name: String,
+    output: Arc<Mutex<Option<Result<T, TaskError>>>>,
+    done: Arc<watch::Sender<Option<bool>>>,
+}
+
+// derive would only implement clone for T: Clone
+impl<T> Clone for TaskHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            output: self.output.clone(),
+            done: self.done.clone(),
+        }
+    }
+}
+
+impl<T> TaskHandle<T> {
+    pub(crate) fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            output: Arc::new(Mutex::new(None)),
+            done: Arc::new(watch::channel(None).0),
+        }
+    }
+
+    /// the message of the widget the task was added with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// whether the task has finished, successfully or not
+    pub fn is_done(&self) -> bool {
+        self.done.borrow().is_some()
+    }
+
+    /// stores the output of the task & lets everything waiting on it know
+    pub(crate) fn finish(&self, output: Result<T, TaskError>) {
+        let succeeded = output.is_ok();
+        *self.output.lock().unwrap() = Some(output);
+        self.done.send_replace(Some(succeeded));
+    }
+
+    /// a type erased version of this handle, for waiting on it
+    pub fn dependency(&self) -> Dependency {
+        Dependency {
+            name: self.name.clone(),
+            done: self.done.subscribe(),
+        }
+    }
+}
+
+impl<T: Clone> TaskHandle<T> {
+    /// a copy of the output of the task, if its done
+    pub fn result(&self) -> Option<Result<T, TaskError>> {
+        self.output.lock().unwrap().clone()
+    }
+
+    /// consumes the handle & gives back the output of the task, if its done
+    ///
+    /// other handles to the same task & tasks depending on it still see the output,
+    /// it only gets moved out instead of copied when this was the last handle
+    pub fn take(self) -> Option<Result<T, TaskError>> {
+        match Arc::try_unwrap(self.output) {
+            Ok(output) => output.into_inner().unwrap(),
+            Err(output) => output.lock().unwrap().clone(),
+        }
+    }
+
+    /// waits for the task to finish, for use inside of other tasks
+    pub async fn wait(&self) -> Result<T, TaskError> {
+        self.dependency().wait().await;
+        self.result()
+            .unwrap_or_else(|| Err(TaskError::Skipped(format!("{} never finished", self.name))))
+    }
+}
+
+/// a task that has to finish before another one can start
+#[derive(Debug, Clone)]
+pub struct Dependency {
+    pub name: String,
+    done: watch::Receiver<Option<bool>>,
+}
+
+impl Dependency {
+    pub fn is_done(&self) -> bool {
+        self.done.borrow().is_some()
+    }
+
+    /// waits for the task to finish
+    ///
+    /// returns whether it succeeded
+    pub async fn wait(&self) -> bool {
+        let mut done = self.done.clone();
+        loop {
+            if let Some(succeeded) = *done.borrow() {
+                return succeeded;
+            }
+            // the task is gone without ever finishing
+            if done.changed().await.is_err() {
+                return false;
+            }
+        }
+    }
+}
+
+/// something a task can depend on, a `TaskHandle` or a tuple or `Vec` of them
+pub trait Dependencies: Send + 'static {
+    /// what the task gets passed once everything is done
+    type Output: Send + 'static;
+
+    /// every task that has to finish first
+    fn dependencies(&self) -> Vec<Dependency>;
+
+    /// the outputs of the tasks, only valid once they are all done
+    fn outputs(&self) -> Result<Self::Output, TaskError>;
+}
+
+impl Dependencies for () {
+    type Output = ();
+
+    fn dependencies(&self) -> Vec<Dependency> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Result<(), TaskError> {
+        Ok(())
+    }
+}
+
+impl<T: Clone + Send + 'static> Dependencies for TaskHandle<T> {
+    type Output = T;
+
+    fn dependencies(&self) -> Vec<Dependency> {
+        vec![self.dependency()]
+    }
+
+    fn outputs(&self) -> Result<T, TaskError> {
+        match &*self.output.lock().unwrap() {
+            Some(Ok(output)) => Ok(output.clone()),
+            Some(Err(TaskError::Failed(_))) => {
+                Err(TaskError::Skipped(format!("{} failed", self.name)))
+            }
+            Some(Err(TaskError::Cancelled)) => {
+                Err(TaskError::Skipped(format!("{} was cancelled", self.name)))
+            }
+            Some(Err(TaskError::Skipped(_))) => {
+                Err(TaskError::Skipped(format!("{} was skipped", self.name)))
+            }
+            None => Err(TaskError::Skipped(format!("{} never finished", self.name))),
+        }
+    }
+}
+
+impl<D: Dependencies> Dependencies for Vec<D> {
+    type Output = Vec<D::Output>;
+
+    fn dependencies(&self) -> Vec<Dependency> {
+        self.iter().flat_map(Dependencies::dependencies).collect()
+    }
+
+    fn outputs(&self) -> Result<Vec<D::Output>, TaskError> {
+        self.iter().map(Dependencies::outputs).collect()
+    }
+}
+
+macro impl_dependencies($($name:ident $idx:tt),*) {
+    impl<$($name: Dependencies),*> Dependencies for ($($name,)*) {
+        type Output = ($($name::Output,)*);
+
+        fn dependencies(&self) -> Vec<Dependency> {
+            let mut out = vec![];
+            $(out.extend(self.$idx.dependencies());)*
+            out
+        }
+
+        fn outputs(&self) -> Result<Self::Output, TaskError> {
+            Ok(($(self.$idx.outputs()?,)*))
+        }
+    }
+}
+
+impl_dependencies!(A 0);
+impl_dependencies!(A 0, B 1);
+impl_dependencies!(A 0, B 1, C 2);
+impl_dependencies!(A 0, B 1, C 2, D 3);
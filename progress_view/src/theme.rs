@@ -0,0 +1,206 @@
+use crossterm::style::{Color, Stylize};
+
+/// the characters & colors widgets get drawn with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// the frames of the spinner shown next to running widgets, one every 100ms
+    pub spinner: &'static [&'static str],
+    /// the levels of a sparkline, lowest first
+    pub sparks: &'static [&'static str],
+    pub glyphs: Glyphs,
+    pub colors: Colors,
+}
+
+/// every character that isnt part of a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyphs {
+    /// a finished widget
+    pub done: &'static str,
+    /// a finished widget where something under it failed
+    pub children_failed: &'static str,
+    /// a widget waiting for a slot to run in
+    pub queued: &'static str,
+    pub error: &'static str,
+    pub cancelled: &'static str,
+    pub skipped: &'static str,
+    /// in front of every failed attempt at running a task
+    pub attempt: &'static str,
+    /// the filled in part of a progress bar
+    pub bar_filled: &'static str,
+    /// the tip of the progress bar when it ends halfway through a character
+    pub bar_tip: &'static str,
+    /// in front of every indented widget
+    pub bullet: &'static str,
+    /// the end of anything that got cut off
+    pub ellipsis: &'static str,
+    /// in front of the selected widget in interactive mode
+    pub cursor: &'static str,
+    /// between the tree & the details pane in interactive mode
+    pub separator: &'static str,
+}
+
+/// what everything gets colored, `None` leaves it uncolored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Colors {
+    pub spinner: Option<Color>,
+    pub done: Option<Color>,
+    /// errors & widgets that had something under them fail
+    pub failed: Option<Color>,
+    pub cancelled: Option<Color>,
+    pub skipped: Option<Color>,
+    /// statuses, attempts & anything else that isnt that important
+    pub dim: Option<Color>,
+    /// the filled in part of a progress bar
+    pub bar: Option<Color>,
+}
+
+impl Theme {
+    pub const UNICODE: Theme = Theme {
+        spinner: &["⠋", "⠙", "⠸", "⣠", "⣄", "⡆"],
+        sparks: &["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"],
+        glyphs: Glyphs {
+            done: "✓",
+            children_failed: "✗",
+            queued: "…",
+            error: "⚠️",
+            cancelled: "⊘",
+            skipped: "↷",
+            attempt: "↻",
+            bar_filled: "━",
+            bar_tip: "╸",
+            bullet: "•",
+            ellipsis: "…",
+            cursor: "›",
+            separator: "│",
+        },
+        colors: Colors {
+            spinner: Some(Color::White),
+            done: Some(Color::Green),
+            failed: Some(Color::Red),
+            cancelled: Some(Color::Yellow),
+            skipped: Some(Color::DarkGrey),
+            dim: Some(Color::DarkGrey),
+            bar: Some(Color::Green),
+        },
+    };
+
+    /// for terminals & fonts that cant draw anything fancier than ascii
+    pub const ASCII: Theme = Theme {
+        spinner: &["|", "/", "-", "\\"],
+        sparks: &["_", ".", "-", "=", "*", "#"],
+        glyphs: Glyphs {
+            done: "+",
+            children_failed: "x",
+            queued: ".",
+            error: "!",
+            cancelled: "-",
+            skipped: ">",
+            attempt: "~",
+            bar_filled: "=",
+            bar_tip: "-",
+            bullet: "*",
+            ellipsis: "...",
+            cursor: ">",
+            separator: "|",
+        },
+        colors: Theme::UNICODE.colors,
+    };
+
+    /// the unicode theme without any colors
+    pub const NO_COLOR: Theme = Theme::UNICODE.without_colors();
+
+    /// looks up one of the built in themes, "unicode", "ascii" or "no-color"
+    pub fn named(name: &str) -> Option<Theme> {
+        match name {
+            "unicode" => Some(Theme::UNICODE),
+            "ascii" => Some(Theme::ASCII),
+            "no-color" => Some(Theme::NO_COLOR),
+            _ => None,
+        }
+    }
+
+    /// this theme, but without any colors
+    pub const fn without_colors(self) -> Theme {
+        Theme {
+            colors: Colors {
+                spinner: None,
+                done: None,
+                failed: None,
+                cancelled: None,
+                skipped: None,
+                dim: None,
+                bar: None,
+            },
+            ..self
+        }
+    }
+
+    /// strips the colors out if the `NO_COLOR` environment variable is set
+    ///
+    /// see https://no-color.org
+    pub fn respect_no_color(self) -> Theme {
+        match std::env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => self.without_colors(),
+            _ => self,
+        }
+    }
+
+    /// colors `text` with `color`, if there is one
+    pub fn paint(&self, text: &str, color: Option<Color>) -> String {
+        match color {
+            Some(color) => text.with(color).to_string(),
+            None => text.to_string(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::UNICODE.respect_no_color()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::strip_escapes;
+    use crate::widget::{Context, Widget};
+
+    #[test]
+    fn test_named_themes() {
+        assert_eq!(Theme::named("ascii"), Some(Theme::ASCII));
+        assert_eq!(Theme::named("comic sans"), None);
+
+        let theme = Theme::named("no-color").unwrap();
+        assert_eq!(theme.glyphs, Theme::UNICODE.glyphs);
+        assert_eq!(theme.paint("done", theme.colors.done), "done");
+        assert_ne!(
+            Theme::UNICODE.paint("done", Theme::UNICODE.colors.done),
+            "done"
+        );
+    }
+
+    #[test]
+    fn test_ascii_theme() {
+        let ctx = Context::new(std::time::SystemTime::now(), Some(30), &Theme::ASCII);
+
+        let mut task = Widget::new_task("a task with a long name", 0);
+        task.set_done();
+        task.attempts.push("502".to_string());
+        let lines = task.render(&ctx);
+        assert_eq!(strip_escapes(&lines[0]), "[+] a task with a long name");
+        assert_eq!(strip_escapes(&lines[1]), "    ~ attempt 1: 502");
+
+        let mut progress = Widget::new_progress("a progress bar", 0, 20);
+        progress.update_discrete_progress(5);
+        progress.set_error("404");
+        let line = strip_escapes(&progress.render(&ctx)[0]);
+        assert_eq!(line, "[!] a progress bar: 404");
+
+        let mut progress = Widget::new_progress("progress", 0, 4);
+        progress.update_discrete_progress(1);
+        progress.active = false;
+        let line = strip_escapes(&progress.render(&ctx)[0]);
+        assert_eq!(line, "[ ] [===[1/4]    ] progress");
+    }
+}
@@ -1,14 +1,63 @@
+use crate::widget::{Outcome, Widget};
+
 #[derive(Debug, Clone)]
 pub struct WidgetUpdate {
     pub update_type: Update,
+    /// the id of the widget to update
     pub index: usize,
 }
 
 #[derive(Debug, Clone)]
 pub enum Update {
     SetActive,
+    /// marks the widget as waiting for a free slot to run in
+    SetQueued,
     SetDone,
+    /// turns the widget into an error widget
+    SetError(String),
+    /// marks the widget as cancelled
+    SetCancelled,
+    /// marks the widget as skipped, because something it depends on didnt succeed
+    SetSkipped(String),
     SetMessage(String),
+    /// sets the progress of a `Progress` widget
+    SetProgress(usize),
+    /// adds to the progress of a `Progress` widget
+    IncrementProgress(usize),
+    /// sets the total of a `Progress` widget
+    SetTotal(usize),
+    /// sets the progress of a `Percentage` widget, from 0.0 to 1.0
+    SetPercentage(f32),
+    /// counts another item of a `Counter` widget
+    Count(Outcome),
+    /// adds to the bytes transferred by a `Bytes` widget
+    AddBytes(u64),
+    /// sets the total of a `Bytes` widget
+    SetBytesTotal(Option<u64>),
+    /// adds a row to the bottom of a `Table` widget
+    AddRow(Vec<String>),
+    /// replaces the row of a `Table` widget that starts with the same cell, or adds it
+    SetRow(Vec<String>),
+    /// adds a value to the end of a `Sparkline` widget
+    PushValue(f32),
+    /// replaces all of the values of a `Sparkline` widget
+    SetValues(Vec<f32>),
+    /// sets the value of a bar of a `Histogram` widget, adding it if its new
+    SetBar {
+        label: String,
+        value: f32,
+    },
+    /// adds a widget as the last child of this one, `id` is the id to give it
+    AddChild {
+        id: usize,
+        widget: Box<Widget>,
+    },
+    /// sets the status shown after the message, like "retry 2/5 in 4s"
+    SetStatus(Option<String>),
+    /// records a failed attempt at running the task
+    AddAttempt(String),
+    /// adds a line to the widgets log
+    Log(String),
 }
 
 impl Update {
@@ -1,9 +1,10 @@
-use crossterm::style::Stylize;
+use crate::output::ellipsize_escaped;
+use crate::table::{self, Column};
+use crate::theme::Theme;
+use crossterm::style::Color;
+use unicode_width::UnicodeWidthStr;
 
-use std::{
-    default::default,
-    time,
-};
+use std::{collections::VecDeque, default::default, time};
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub enum WidgetType {
@@ -19,23 +20,141 @@ pub enum WidgetType {
         progress: usize,
         total: usize,
     },
+    // [⠦] [       ━━━━━[ 12]           ] {message}
+    /// a progress widget that doesnt know its total yet, setting the total turns it into a
+    /// `Progress` widget
+    Indeterminate {
+        progress: usize,
+    },
+    // [⠦] [━━━━━━━━[28✓ 3✗ 1↷ of 40]━      ] {message}
+    /// counts how many items ended up with each `Outcome`
+    Counter {
+        succeeded: usize,
+        failed: usize,
+        skipped: usize,
+        total: usize,
+    },
+    // [⠦] [━━━━━╸  [1.2/4.0 MiB]         ] {message}
+    Bytes {
+        transferred: u64,
+        /// `None` if there is no telling how many bytes there are going to be
+        total: Option<u64>,
+    },
     // [⠦] {message}
     Task {
         done: bool,
     },
+    // {message}
+    // repo       stars  language
+    // tokio      24301  Rust
+    Table(table::Table),
+    // [▁▂▃▅▇▅▃▂      ] {message}
+    Sparkline {
+        values: Vec<f32>,
+    },
+    // {message}
+    // rust    ━━━━━━━━━━━━╸ 12
+    // python  ━━━━━━        6
+    Histogram {
+        /// the label & value of every bar
+        bars: Vec<(String, f32)>,
+    },
     // [⚠️] {message}
     Error,
+    // [⊘] {message}
+    Cancelled,
+    // [↷] {message}
+    Skipped,
 }
 
-#[derive(Default, Debug, PartialEq)]
+/// how one of the items counted by a counter widget turned out
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// what a widget needs to know to render itself
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    pub time: time::SystemTime,
+    /// how many columns the widget has to work with, `None` if there is no limit
+    pub width: Option<usize>,
+    pub theme: &'a Theme,
+    /// what elapsed times & etas are measured up to
+    pub now: time::Instant,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(time: time::SystemTime, width: Option<usize>, theme: &'a Theme) -> Self {
+        Self {
+            time,
+            width,
+            theme,
+            now: time::Instant::now(),
+        }
+    }
+
+    /// how wide progress bars should be
+    pub fn bar_width(&self) -> usize {
+        match self.width {
+            Some(width) => (width / 3).clamp(12, 40),
+            None => 25,
+        }
+    }
+
+    /// how many columns are left after `used` of them have been taken up
+    pub fn remaining(&self, used: usize) -> Option<usize> {
+        self.width.map(|width| width.saturating_sub(used))
+    }
+}
+
+/// where a widget is at, ignoring how far along it is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum State {
+    Inactive,
+    Queued,
+    Active,
+    Done,
+    Failed,
+    Cancelled,
+    Skipped,
+}
+
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct Widget {
     pub widget: WidgetType,
     pub active: bool,
+    /// whether the widgets task is waiting for a slot to run in
+    pub queued: bool,
     pub message: String,
     pub indent: usize,
+    /// whether any of the widgets under this one failed
+    pub children_failed: bool,
+    /// shown after the message, like "retry 2/5 in 4s"
+    pub status: Option<String>,
+    /// the errors from every failed attempt at running this widgets task
+    pub attempts: Vec<String>,
+    /// the last `Widget::MAX_LOGS` lines logged to this widget
+    pub logs: VecDeque<String>,
+    /// when the widget became active
+    pub started: Option<time::Instant>,
+    /// when the widget finished
+    pub finished: Option<time::Instant>,
+    /// how fast the widget is making progress
+    pub rate: Rate,
+    /// how the widgets children did, set when they are collapsed into this one
+    /// like "312 done, 2 failed"
+    pub summary: Option<String>,
 }
 
 impl Widget {
+    /// how many log lines a widget holds on to
+    pub const MAX_LOGS: usize = 20;
+    /// how many of the last log lines get shown under running & failed widgets
+    pub const SHOWN_LOGS: usize = 3;
+
     /// Creates a new text widget off of a WidgetType, message, and indent
     fn new<T: Into<String>>(message: T, indent: usize, widget: WidgetType) -> Self {
         Self {
@@ -61,6 +180,56 @@ impl Widget {
         Self::new(message, indent, WidgetType::Percentage { progress: 0.0 })
     }
 
+    /// Create a new progress widget that doesnt know its total yet
+    pub fn new_indeterminate<T: Into<String>>(message: T, indent: usize) -> Self {
+        Self::new(message, indent, WidgetType::Indeterminate { progress: 0 })
+    }
+
+    /// Create a new table widget with the headers & alignment of `columns`
+    pub fn new_table<T: Into<String>>(message: T, indent: usize, columns: Vec<Column>) -> Self {
+        Self::new(
+            message,
+            indent,
+            WidgetType::Table(table::Table::new(columns)),
+        )
+    }
+
+    /// Create a new sparkline widget
+    pub fn new_sparkline<T: Into<String>>(message: T, indent: usize) -> Self {
+        Self::new(message, indent, WidgetType::Sparkline { values: vec![] })
+    }
+
+    /// Create a new histogram widget
+    pub fn new_histogram<T: Into<String>>(message: T, indent: usize) -> Self {
+        Self::new(message, indent, WidgetType::Histogram { bars: vec![] })
+    }
+
+    /// Create a new widget counting the outcomes of `total` items
+    pub fn new_counter<T: Into<String>>(message: T, indent: usize, total: usize) -> Self {
+        Self::new(
+            message,
+            indent,
+            WidgetType::Counter {
+                succeeded: 0,
+                failed: 0,
+                skipped: 0,
+                total,
+            },
+        )
+    }
+
+    /// Create a new widget counting bytes, `total` is `None` if its not known up front
+    pub fn new_bytes<T: Into<String>>(message: T, indent: usize, total: Option<u64>) -> Self {
+        Self::new(
+            message,
+            indent,
+            WidgetType::Bytes {
+                transferred: 0,
+                total,
+            },
+        )
+    }
+
     /// Create a new task widget
     pub fn new_task<T: Into<String>>(message: T, indent: usize) -> Self {
         Self::new(message, indent, WidgetType::Task { done: false })
@@ -78,31 +247,218 @@ impl Widget {
             ..
         } = self.widget
         {
-            self.active = true;
             *p = progress;
+            self.activate();
+            self.sample_rate();
         }
     }
 
-    /// Update the progress of a discrete progress widget
+    /// Update the progress of a discrete or indeterminate progress widget
     pub fn update_discrete_progress(&mut self, progress: usize) {
         if let WidgetType::Progress {
             progress: ref mut p,
             ..
+        }
+        | WidgetType::Indeterminate {
+            progress: ref mut p,
         } = self.widget
         {
-            self.active = true;
             *p = progress;
+            self.activate();
+            self.sample_rate();
+        }
+    }
+
+    /// Increment the progress of a discrete or indeterminate progress widget
+    pub fn increment_progress(&mut self, amount: usize) {
+        if let WidgetType::Progress {
+            progress: ref mut p,
+            ..
+        }
+        | WidgetType::Indeterminate {
+            progress: ref mut p,
+        } = self.widget
+        {
+            *p += amount;
+            self.activate();
+            self.sample_rate();
+        }
+    }
+
+    /// Add to the bytes transferred by a bytes widget
+    pub fn add_bytes(&mut self, amount: u64) {
+        if let WidgetType::Bytes {
+            transferred: ref mut t,
+            ..
+        } = self.widget
+        {
+            *t += amount;
+            self.activate();
+            self.sample_rate();
         }
     }
 
+    /// Update the total of a bytes widget
+    pub fn update_bytes_total(&mut self, total: Option<u64>) {
+        if let WidgetType::Bytes {
+            total: ref mut t, ..
+        } = self.widget
+        {
+            *t = total;
+        }
+    }
+
+    /// Update the total of a discrete progress widget
+    ///
+    /// an indeterminate widget turns into a discrete one, keeping its progress
+    pub fn update_total(&mut self, total: usize) {
+        match self.widget {
+            WidgetType::Progress {
+                total: ref mut t, ..
+            }
+            | WidgetType::Counter {
+                total: ref mut t, ..
+            } => *t = total,
+            WidgetType::Indeterminate { progress } => {
+                self.widget = WidgetType::Progress { progress, total }
+            }
+            _ => {}
+        }
+    }
+
+    /// Count another item of a counter widget as having turned out as `outcome`
+    pub fn count(&mut self, outcome: Outcome) {
+        if let WidgetType::Counter {
+            ref mut succeeded,
+            ref mut failed,
+            ref mut skipped,
+            ..
+        } = self.widget
+        {
+            match outcome {
+                Outcome::Succeeded => *succeeded += 1,
+                Outcome::Failed => *failed += 1,
+                Outcome::Skipped => *skipped += 1,
+            }
+            self.activate();
+            self.sample_rate();
+        }
+    }
+
+    /// Add a row to the bottom of a table widget
+    pub fn add_row(&mut self, row: Vec<String>) {
+        if let WidgetType::Table(ref mut table) = self.widget {
+            table.add_row(row);
+        }
+    }
+
+    /// Replace the row of a table widget that starts with the same cell, or add it
+    pub fn set_row(&mut self, row: Vec<String>) {
+        if let WidgetType::Table(ref mut table) = self.widget {
+            table.set_row(row);
+        }
+    }
+
+    /// Add a value to the end of a sparkline widget
+    pub fn push_value(&mut self, value: f32) {
+        if let WidgetType::Sparkline { ref mut values } = self.widget {
+            values.push(value);
+        }
+    }
+
+    /// Replace all of the values of a sparkline widget
+    pub fn set_values(&mut self, values: Vec<f32>) {
+        if let WidgetType::Sparkline { values: ref mut v } = self.widget {
+            *v = values;
+        }
+    }
+
+    /// Set the value of the bar labelled `label` of a histogram widget, adding it if its new
+    pub fn set_bar(&mut self, label: String, value: f32) {
+        if let WidgetType::Histogram { ref mut bars } = self.widget {
+            match bars.iter_mut().find(|(existing, _)| *existing == label) {
+                Some((_, existing)) => *existing = value,
+                None => bars.push((label, value)),
+            }
+        }
+    }
+
+    /// whether the widget only shows something, without a state of its own
+    pub fn is_static(&self) -> bool {
+        use WidgetType::*;
+        matches!(
+            self.widget,
+            Text | Table(_) | Sparkline { .. } | Histogram { .. }
+        )
+    }
+
     /// Update whether a task is done
     pub fn update_task_done(&mut self, done: bool) {
         if let WidgetType::Task {
             done: ref mut d, ..
         } = self.widget
         {
-            self.active = true;
             *d = done;
+            self.activate();
+        }
+    }
+
+    /// marks the widget as active & starts its clock
+    pub fn activate(&mut self) {
+        self.active = true;
+        if self.started.is_none() {
+            let now = time::Instant::now();
+            self.started = Some(now);
+            self.rate.start(now);
+        }
+    }
+
+    /// stops the widgets clock
+    fn finish(&mut self) {
+        self.activate();
+        self.finished.get_or_insert_with(time::Instant::now);
+    }
+
+    /// how long the widget has been running for, or took if its finished
+    pub fn elapsed(&self, now: time::Instant) -> Option<time::Duration> {
+        let end = self.finished.unwrap_or(now);
+        self.started
+            .map(|started| end.saturating_duration_since(started))
+    }
+
+    /// how far along the widget is, in items for progress widgets,
+    /// bytes for bytes widgets and fractions for percentage widgets
+    fn progress(&self) -> Option<f32> {
+        match self.widget {
+            WidgetType::Percentage { progress } => Some(progress),
+            WidgetType::Progress { progress, .. } => Some(progress as f32),
+            WidgetType::Indeterminate { progress } => Some(progress as f32),
+            WidgetType::Counter {
+                succeeded,
+                failed,
+                skipped,
+                ..
+            } => Some((succeeded + failed + skipped) as f32),
+            WidgetType::Bytes { transferred, .. } => Some(transferred as f32),
+            _ => None,
+        }
+    }
+
+    /// how far along the widget has to get, if thats known
+    fn total(&self) -> Option<f32> {
+        match self.widget {
+            WidgetType::Percentage { .. } => Some(1.0),
+            WidgetType::Progress { total, .. } => Some(total as f32),
+            WidgetType::Counter { total, .. } => Some(total as f32),
+            WidgetType::Bytes { total, .. } => total.map(|total| total as f32),
+            _ => None,
+        }
+    }
+
+    /// updates the rate with however far along the widget is now
+    fn sample_rate(&mut self) {
+        if let Some(progress) = self.progress() {
+            self.rate.sample(time::Instant::now(), progress);
         }
     }
 
@@ -110,13 +466,27 @@ impl Widget {
     pub fn is_done(&self) -> bool {
         use WidgetType::*;
         match self.widget {
-            Text { .. } => true,
+            Text | Table(_) | Sparkline { .. } | Histogram { .. } | Error | Cancelled | Skipped => {
+                true
+            }
             Percentage { progress, .. } => progress >= 1.0,
             Progress {
                 progress, total, ..
             } => progress >= total,
+            // theres no telling until its total is known
+            Indeterminate { .. } => false,
+            // a total of 0 means it hasnt been set yet, unless the task is over
+            Counter {
+                succeeded,
+                failed,
+                skipped,
+                total,
+            } => self.finished.is_some() || total > 0 && succeeded + failed + skipped >= total,
+            // a transfer thats cut short is still done once its task is
+            Bytes { transferred, total } => {
+                self.finished.is_some() || total.is_some_and(|total| transferred >= total)
+            }
             Task { done, .. } => done,
-            Error { .. } => true,
         }
     }
 
@@ -125,20 +495,38 @@ impl Widget {
     pub fn is_active(&self) -> bool {
         use WidgetType::*;
         match self.widget {
-            Text { .. } => true,
-            Percentage { .. } => self.active,
-            Progress { .. } => self.active,
-            Task { .. } => self.active,
-            Error { .. } => true,
+            Text | Table(_) | Sparkline { .. } | Histogram { .. } | Error | Cancelled | Skipped => {
+                true
+            }
+            Percentage { .. }
+            | Progress { .. }
+            | Indeterminate { .. }
+            | Counter { .. }
+            | Bytes { .. }
+            | Task { .. } => self.active,
+        }
+    }
+
+    /// where this widget is at
+    pub fn state(&self) -> State {
+        match self.widget {
+            WidgetType::Error => State::Failed,
+            WidgetType::Cancelled => State::Cancelled,
+            WidgetType::Skipped => State::Skipped,
+            _ if self.is_done() => State::Done,
+            _ if self.active => State::Active,
+            _ if self.queued => State::Queued,
+            _ => State::Inactive,
         }
     }
 
     /// sets the widget such that it is done
     pub fn set_done(&mut self) {
         use WidgetType::*;
-        self.active = true;
+        self.finish();
         match self.widget {
-            Text { .. } => {}
+            Text | Table(_) | Sparkline { .. } | Histogram { .. } | Error | Cancelled | Skipped => {
+            }
             Percentage {
                 ref mut progress, ..
             } => *progress = 1.0,
@@ -147,107 +535,851 @@ impl Widget {
                 total,
                 ..
             } => *progress = total,
+            // however far it got is how far there was to go
+            Indeterminate { progress } => {
+                self.widget = Progress {
+                    progress,
+                    total: progress,
+                }
+            }
+            // whatever got counted is all there was
+            Counter {
+                succeeded,
+                failed,
+                skipped,
+                ref mut total,
+            } => *total = succeeded + failed + skipped,
+            // a known total stays put so a short transfer shows as short,
+            // otherwise whatever made it through is all there was
+            Bytes {
+                transferred,
+                ref mut total,
+            } => {
+                total.get_or_insert(transferred);
+            }
             Task { ref mut done, .. } => *done = true,
-            Error { .. } => {}
         }
         // dbg!(&self);
     }
 
+    /// turns this widget into an error widget displaying `error`
+    pub fn set_error<T: Into<String>>(&mut self, error: T) {
+        self.finish();
+        self.widget = WidgetType::Error;
+        self.message = format!("{}: {}", self.message, error.into());
+    }
+
+    /// marks this widget as cancelled
+    pub fn set_cancelled(&mut self) {
+        self.finish();
+        self.widget = WidgetType::Cancelled;
+    }
+
+    /// marks this widget as skipped because of `reason`
+    pub fn set_skipped<T: Into<String>>(&mut self, reason: T) {
+        self.finish();
+        self.widget = WidgetType::Skipped;
+        self.message = format!("{} (skipped, {})", self.message, reason.into());
+    }
+
+    /// adds `line` to the log, forgetting the oldest line if its full
+    pub fn log<T: Into<String>>(&mut self, line: T) {
+        for line in line.into().lines() {
+            if self.logs.len() == Self::MAX_LOGS {
+                self.logs.pop_front();
+            }
+            self.logs.push_back(line.to_string());
+        }
+    }
+
     /// sets the message of this widget
     pub fn set_message<T: Into<String>>(&mut self, message: T) {
         self.message = message.into();
     }
 
     /// Render the widget
-    pub fn render(&self, time: time::SystemTime) {
+    ///
+    /// returns the lines it takes up, without the indent
+    pub fn render(&self, ctx: &Context) -> Vec<String> {
         use WidgetType::*;
 
-        const SPINNER: [char; 6] = ['⠋', '⠙', '⠸', '⣠', '⣄', '⡆'];
-        let charn = (time.duration_since(time::UNIX_EPOCH).unwrap().as_millis() / 100
+        let theme = ctx.theme;
+        let (glyphs, colors) = (&theme.glyphs, &theme.colors);
+        let charn = (ctx
+            .time
+            .duration_since(time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            / 100
             % usize::MAX as u128) as usize;
         let get_spinner_char = |cond| {
             if self.active {
-                if cond {
-                    "✓".to_string().green()
+                if cond && self.children_failed {
+                    theme.paint(glyphs.children_failed, colors.failed)
+                } else if cond {
+                    theme.paint(glyphs.done, colors.done)
                 } else {
-                    SPINNER[charn % SPINNER.len()].to_string().white()
+                    let spinner = theme.spinner[charn % theme.spinner.len()];
+                    theme.paint(spinner, colors.spinner)
                 }
+            } else if self.queued {
+                theme.paint(glyphs.queued, colors.dim)
             } else {
-                " ".to_string().white()
+                " ".repeat(glyphs.done.chars().count())
             }
         };
+        // `[x] `, for when the glyph is `x`
+        let glyph_width = |glyph: &str| glyph.chars().count() + 3;
 
-        let message = &self.message;
-        match &self.widget {
-            // Lorem ipsum
-            Text => {
-                println!("{message}")
+        // the message gets whatever room is left after everything before it
+        let message = |used| self.fit_message(ctx.remaining(used), ctx);
+        // the elapsed time, rate & eta go between the bar & the message if theres room
+        let stats = |used| match ctx.remaining(used) {
+            Some(width) if width < STATS_WIDTH + 1 + MIN_MESSAGE_WIDTH => (String::new(), 0),
+            _ => {
+                let stats = theme.paint(&self.stats(ctx.now), colors.dim);
+                (stats + " ", STATS_WIDTH + 1)
             }
-            // [⠦] [━━━━━━━━[ 55.0%]        ] Lorem ipsum
+        };
+        let bar_width = ctx.bar_width();
+        let task_width = glyph_width(glyphs.done);
+
+        let line = match &self.widget {
+            // Lorem ipsum
+            Text => message(0),
+            // [⠦] [━━━━━━━━[ 55.0%]        ]   14.2s      4.2%/s    ETA 3.5s Lorem ipsum
             Percentage { progress } => {
                 let spinner_char = get_spinner_char(*progress >= 1.0);
-                println!(
-                    "[{spinner_char}] [{center}] {message}",
-                    center = percentage(*progress, 25, format!("[{:3.1}%]", *progress * 100.0))
+                let used = task_width + bar_width + 3;
+                let (stats, stats_width) = stats(used);
+                format!(
+                    "[{spinner_char}] [{center}] {stats}{message}",
+                    center = percentage(
+                        *progress,
+                        bar_width,
+                        format!("[{:3.1}%]", *progress * 100.0),
+                        theme
+                    ),
+                    message = message(used + stats_width)
                 )
             }
-            // [⠦] [━━━━━╸  [ 5/20]         ] Lorem ipsum
+            // [⠦] [━━━━━╸  [ 5/20]         ]   14.2s   12 items/s    ETA 3.5s Lorem ipsum
             Progress { progress, total } => {
                 let spinner_char = get_spinner_char(*progress > *total);
                 let digits = (*total as f32).log10().ceil() as usize;
                 let percent_progress = *progress as f32 / *total as f32;
-                println!(
-                    "[{spinner_char}] [{center}] {message}",
+                let used = task_width + bar_width + 3;
+                let (stats, stats_width) = stats(used);
+                format!(
+                    "[{spinner_char}] [{center}] {stats}{message}",
                     center = percentage(
                         percent_progress,
-                        25,
-                        format!("[{progress:digits$}/{total}]")
-                    )
+                        bar_width,
+                        format!("[{progress:digits$}/{total}]"),
+                        theme
+                    ),
+                    message = message(used + stats_width)
+                )
+            }
+            // [⠦] [       ━━━━━[ 12]           ]   14.2s   12 items/s             Lorem ipsum
+            Indeterminate { progress } => {
+                let spinner_char = get_spinner_char(false);
+                let used = task_width + bar_width + 3;
+                let (stats, stats_width) = stats(used);
+                format!(
+                    "[{spinner_char}] [{center}] {stats}{message}",
+                    center = bounce(charn, bar_width, format!("[{progress}]"), theme),
+                    message = message(used + stats_width)
+                )
+            }
+            // [⠦] [━━━━━━━━[28✓ 3✗ 1↷ of 40]━      ]   14.2s   12 items/s    ETA 3.5s Lorem ipsum
+            Counter {
+                succeeded,
+                failed,
+                skipped,
+                total,
+            } => {
+                let spinner_char = get_spinner_char(self.is_done());
+                let mut center = format!("[{succeeded}{}", glyphs.done);
+                if *failed > 0 {
+                    center += &format!(" {failed}{}", glyphs.children_failed);
+                }
+                if *skipped > 0 {
+                    center += &format!(" {skipped}{}", glyphs.skipped);
+                }
+                center += &format!(" of {total}]");
+                let of_total = |count| count as f32 / (*total).max(1) as f32;
+                let segments = [
+                    (of_total(*succeeded), colors.bar),
+                    (of_total(*failed), colors.failed),
+                    (of_total(*skipped), colors.skipped),
+                ];
+                let used = task_width + bar_width + 3;
+                let (stats, stats_width) = stats(used);
+                format!(
+                    "[{spinner_char}] [{center}] {stats}{message}",
+                    center = segmented(&segments, bar_width, center, theme),
+                    message = message(used + stats_width)
+                )
+            }
+            // [⠦] [━━━━━╸  [1.2/4.0 MiB]         ]   14.2s    340 KiB/s    ETA 3.5s Lorem ipsum
+            Bytes { transferred, total } => {
+                let spinner_char = get_spinner_char(self.is_done());
+                let (progress, center) = match total {
+                    Some(total) => (
+                        *transferred as f32 / (*total).max(1) as f32,
+                        format!("[{}]", format_bytes_of(*transferred, *total)),
+                    ),
+                    // theres no telling how far along it is
+                    None => (0.0, format!("[{}]", format_bytes(*transferred as f32))),
+                };
+                let used = task_width + bar_width + 3;
+                let (stats, stats_width) = stats(used);
+                format!(
+                    "[{spinner_char}] [{center}] {stats}{message}",
+                    center = percentage(progress, bar_width, center, theme),
+                    message = message(used + stats_width)
                 )
             }
             Task { done } => {
                 let spinner_char = get_spinner_char(*done);
-                println!("[{spinner_char}] {message}")
+                format!("[{spinner_char}] {}", message(task_width))
+            }
+            // Lorem ipsum
+            // repo       stars  language
+            // tokio      24301  Rust
+            Table(table) => {
+                let pad = " ".repeat(self.indent * 3);
+                let mut lines = vec![message(0)];
+                for (n, line) in table
+                    .render(ctx.width, glyphs.ellipsis)
+                    .into_iter()
+                    .enumerate()
+                {
+                    let line = match n {
+                        0 => theme.paint(&line, colors.dim),
+                        _ => line,
+                    };
+                    lines.push(format!("{pad}{line}"));
+                }
+                return lines;
+            }
+            // [▁▂▃▅▇▅▃▂      ] Lorem ipsum
+            Sparkline { values } => {
+                let line = sparkline(values, bar_width, theme.sparks);
+                let pad = bar_width - line.chars().count();
+                let line = theme.paint(&line, colors.bar);
+                format!("[{line}{:pad$}] {}", "", message(bar_width + 3))
+            }
+            // Lorem ipsum
+            // rust    ━━━━━━━━━━━━╸ 12
+            // python  ━━━━━━        6
+            Histogram { bars } => {
+                let pad = " ".repeat(self.indent * 3);
+                let mut lines = vec![message(0)];
+                for line in histogram(bars, ctx, theme) {
+                    lines.push(format!("{pad}{line}"));
+                }
+                return lines;
             }
             // [⚠️] Uh-oh someone did an oopsie
             Error => {
-                println!("{}", format!("[⚠️] {message}").red())
+                let used = glyph_width(glyphs.error);
+                let line = format!("[{}] {}", glyphs.error, message(used));
+                theme.paint(&line, colors.failed)
+            }
+            // [⊘] Lorem ipsum
+            Cancelled => {
+                let used = glyph_width(glyphs.cancelled);
+                let line = format!("[{}] {}", glyphs.cancelled, message(used));
+                theme.paint(&line, colors.cancelled)
+            }
+            // [↷] Lorem ipsum (skipped, dolor sit failed)
+            Skipped => {
+                let used = glyph_width(glyphs.skipped);
+                let line = format!("[{}] {}", glyphs.skipped, message(used));
+                theme.paint(&line, colors.skipped)
             }
+        };
+
+        let mut lines = vec![line];
+        // ↻ attempt 1: timed out after 10s
+        for (n, error) in self.attempts.iter().enumerate() {
+            let line = format!("{} attempt {}: {error}", glyphs.attempt, n + 1);
+            let line = ellipsize(&line, ctx.remaining(task_width), glyphs.ellipsis);
+            lines.push(format!(
+                "{:pad$}{}",
+                "",
+                theme.paint(&line, colors.dim),
+                pad = self.indent * 3 + task_width
+            ));
+        }
+
+        // the logs are only worth the space while the widget is running or if it failed
+        let logs = match self.state() {
+            State::Active | State::Failed => self.logs.len().min(Self::SHOWN_LOGS),
+            _ => 0,
+        };
+        for line in self.logs.iter().skip(self.logs.len() - logs) {
+            let line = ellipsize(line, ctx.remaining(task_width), glyphs.ellipsis);
+            lines.push(format!(
+                "{:pad$}{}",
+                "",
+                theme.paint(&line, colors.dim),
+                pad = self.indent * 3 + task_width
+            ));
         }
+        lines
     }
+
+    /// the elapsed time, rate & eta of a progress bar, `STATS_WIDTH` wide
+    ///
+    /// `  14.2s    12 items/s    ETA 3.5s`
+    fn stats(&self, now: time::Instant) -> String {
+        let elapsed = self.elapsed(now).map(format_duration).unwrap_or_default();
+        let (rate, eta) = match (self.progress(), self.elapsed(now)) {
+            // how fast it went on average
+            (Some(progress), Some(elapsed)) if self.finished.is_some() => {
+                let rate = progress / elapsed.as_secs_f32().max(f32::EPSILON);
+                (self.format_rate(rate), String::new())
+            }
+            (Some(progress), _) => match self.rate.per_sec() {
+                Some(rate) => {
+                    let eta = match self.total() {
+                        Some(total) if rate > 0.0 => {
+                            let eta = ((total - progress).max(0.0) / rate).min(1e7);
+                            format!(
+                                "ETA {}",
+                                format_duration(time::Duration::from_secs_f32(eta))
+                            )
+                        }
+                        _ => String::new(),
+                    };
+                    (self.format_rate(rate), eta)
+                }
+                None => default(),
+            },
+            _ => default(),
+        };
+        format!("{elapsed:>7} {rate:>13} {eta:>11}")
+    }
+
+    /// `12 items/s` for progress widgets, `340 KiB/s` for bytes widgets,
+    /// `4.2%/s` for percentage widgets
+    fn format_rate(&self, rate: f32) -> String {
+        match self.widget {
+            WidgetType::Percentage { .. } => format!("{:.1}%/s", rate * 100.0),
+            WidgetType::Bytes { .. } => format!("{}/s", format_bytes(rate)),
+            _ if rate >= 10.0 => format!("{rate:.0} items/s"),
+            _ => format!("{rate:.1} items/s"),
+        }
+    }
+
+    /// the message & status of the widget, cut down to fit in `width` columns
+    fn fit_message(&self, width: Option<usize>, ctx: &Context) -> String {
+        let theme = ctx.theme;
+        let ellipsis = theme.glyphs.ellipsis;
+        let message = ellipsize(&self.message, width, ellipsis);
+
+        // progress bars have the elapsed time next to the bar instead
+        let elapsed = match self.widget {
+            WidgetType::Task { .. } => self.elapsed(ctx.now).map(format_duration),
+            _ => None,
+        };
+        let elapsed = match (elapsed, self.finished) {
+            (Some(elapsed), Some(_)) => format!(" in {elapsed}"),
+            (Some(elapsed), None) => format!(" {elapsed}"),
+            (None, _) => String::new(),
+        };
+        let status = match (&self.status, &self.summary) {
+            (Some(status), _) => format!(" ({status}){elapsed}"),
+            // ✓ Fetch repos (312 done, 2 failed) in 14.2s
+            (None, Some(summary)) => format!(" ({summary}){elapsed}"),
+            (None, None) if !elapsed.is_empty() => elapsed.clone(),
+            (None, None) => return message,
+        };
+
+        let room = width.map(|width| width - message.width());
+        if let Some(room) = room.filter(|&room| room < status.width()) {
+            // theres no point in showing part of a time, or an ellipsis all on its own
+            if status == elapsed || room <= ellipsis.width() + 1 {
+                return message;
+            }
+        }
+        let status = ellipsize(&status, room, ellipsis);
+        match status.is_empty() {
+            true => message,
+            false => format!("{message}{}", theme.paint(&status, theme.colors.dim)),
+        }
+    }
+}
+
+/// how wide `Widget::stats` is
+const STATS_WIDTH: usize = 33;
+/// the stats get left out if they wouldnt leave at least this much room for the message
+const MIN_MESSAGE_WIDTH: usize = 20;
+
+/// a rate of progress, smoothed out so it doesnt jump around with every update
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rate {
+    /// the last time the rate was updated, and how far along things were then
+    last: Option<(time::Instant, f32)>,
+    per_sec: Option<f32>,
 }
 
-fn percentage(progress: f32, width: usize, center_msg: String) -> String {
-    let center_width = center_msg.len();
-    let left_width = (width - center_width) / 2;
-    // the chunks of the progress bar that are filled
-    let chunks = (progress * width as f32 * 2.0).floor() as usize;
-    let tick = chunks % 2 == 1;
-    let chunks = chunks / 2;
+impl Rate {
+    /// how many seconds it takes for the rate to mostly catch up with a change in speed
+    const SMOOTHING: f32 = 3.0;
+    /// updates closer together than this many seconds get lumped together
+    const MIN_INTERVAL: f32 = 0.05;
 
-    let mut left = "━".repeat(left_width);
-    let mut right = "".to_string();
+    /// starts measuring from nothing at `now`
+    pub fn start(&mut self, now: time::Instant) {
+        self.last = Some((now, 0.0));
+    }
 
-    // if the progress is less than the left side of the center message
-    if chunks < left_width {
-        left = "━".repeat(chunks);
-        if tick {
-            left.push('╸');
+    /// updates the rate with how far along things were at `now`
+    pub fn sample(&mut self, now: time::Instant, progress: f32) {
+        let Some((last, last_progress)) = self.last else {
+            self.last = Some((now, progress));
+            return;
+        };
+        let dt = now.saturating_duration_since(last).as_secs_f32();
+        if dt < Self::MIN_INTERVAL {
+            return;
         }
+
+        let rate = (progress - last_progress) / dt;
+        // the longer its been, the less the old rate matters
+        let weight = 1.0 - (-dt / Self::SMOOTHING).exp();
+        self.per_sec = Some(match self.per_sec {
+            Some(old) => old + weight * (rate - old),
+            None => rate,
+        });
+        self.last = Some((now, progress));
+    }
+
+    /// how much progress gets made a second
+    pub fn per_sec(&self) -> Option<f32> {
+        self.per_sec
+    }
+}
+
+/// `14.2s`, `2m05s` or `1h02m`
+pub fn format_duration(duration: time::Duration) -> String {
+    let secs = duration.as_secs_f32();
+    match duration.as_secs() {
+        0..60 => format!("{secs:.1}s"),
+        60..3600 => format!("{}m{:02}s", secs as u64 / 60, secs as u64 % 60),
+        _ => format!("{}h{:02}m", secs as u64 / 3600, secs as u64 / 60 % 60),
+    }
+}
+
+const BYTE_UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+/// the biggest unit `bytes` is at least one of, and how many of it that is
+fn byte_unit(bytes: f32) -> (f32, &'static str) {
+    let mut bytes = bytes;
+    let mut unit = 0;
+    while bytes >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+        bytes /= 1024.0;
+        unit += 1;
+    }
+    (bytes, BYTE_UNITS[unit])
+}
+
+/// `512 B`, `1.2 KiB` or `340.0 MiB`
+pub fn format_bytes(bytes: f32) -> String {
+    match byte_unit(bytes) {
+        (bytes, "B") => format!("{bytes:.0} B"),
+        (bytes, unit) => format!("{bytes:.1} {unit}"),
     }
-    // if the progress is greater than the right side of the center message
-    else if chunks >= left_width + center_width {
-        right = "━".repeat(chunks - left_width - center_width);
-        if tick {
-            right.push('╸');
+}
+
+/// `1.2/4.0 MiB`, both in the unit of `total`
+fn format_bytes_of(transferred: u64, total: u64) -> String {
+    let (scaled, unit) = byte_unit(total as f32);
+    let transferred = transferred as f32 * scaled / (total as f32).max(1.0);
+    let total = scaled;
+    match unit {
+        "B" => format!("{transferred:.0}/{total:.0} B"),
+        _ => format!("{transferred:.1}/{total:.1} {unit}"),
+    }
+}
+
+/// cuts `text` down to `width` columns, ending it with `ellipsis` if anything was cut off
+pub fn ellipsize(text: &str, width: Option<usize>, ellipsis: &str) -> String {
+    match width {
+        Some(width) => ellipsize_escaped(text, width, ellipsis),
+        None => text.to_string(),
+    }
+}
+
+fn percentage(progress: f32, width: usize, center_msg: String, theme: &Theme) -> String {
+    let (filled, tip) = (theme.glyphs.bar_filled, theme.glyphs.bar_tip);
+    overlay(width, &center_msg, theme, |width| {
+        // the halves of cells that are filled
+        let halves = (progress * width as f32 * 2.0).floor() as usize;
+        (0..width)
+            .map(|n| match n * 2 + 1 {
+                half if half < halves => (filled, theme.colors.bar),
+                half if half == halves => (tip, theme.colors.bar),
+                _ => (" ", None),
+            })
+            .collect()
+    })
+}
+
+/// lays `center_msg` over the middle of a bar that is `width` cells wide,
+/// or as wide as the message if thats wider
+///
+/// `cells` gets the width of the bar & returns the glyph & color of every cell in it,
+/// the message takes on the color of the cells it covers
+fn overlay<'a, F>(width: usize, center_msg: &str, theme: &Theme, cells: F) -> String
+where
+    F: FnOnce(usize) -> Vec<(&'a str, Option<Color>)>,
+{
+    let center = center_msg.chars().collect::<Vec<_>>();
+    // always leave room for the message in the middle
+    let width = width.max(center.len());
+    let left_width = (width - center.len()) / 2;
+
+    // paint runs of cells with the same color all at once
+    let mut bar = String::new();
+    let mut run = String::new();
+    let mut color = None;
+    for (n, (glyph, cell_color)) in cells(width).into_iter().enumerate() {
+        if cell_color != color {
+            bar += &theme.paint(&run, color);
+            run.clear();
+            color = cell_color;
+        }
+        match n.checked_sub(left_width) {
+            Some(n) if n < center.len() => run.push(center[n]),
+            _ => run.push_str(glyph),
+        }
+    }
+    bar += &theme.paint(&run, color);
+    bar
+}
+
+/// `values` as a line of at most `width` sparks, scaled from the smallest value to the biggest
+///
+/// if there are too many values, neighbouring ones get averaged together
+fn sparkline(values: &[f32], width: usize, sparks: &[&str]) -> String {
+    let buckets = (values.len().max(1) - 1) / width.max(1) + 1;
+    let values = values
+        .chunks(buckets)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect::<Vec<_>>();
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let top = sparks.len().saturating_sub(1);
+    values
+        .iter()
+        .map(|value| match max > min {
+            true => sparks[((value - min) / (max - min) * top as f32).round() as usize],
+            // a flat line
+            false => sparks[0],
+        })
+        .collect()
+}
+
+/// a line for every bar, labels on the left & values on the right,
+/// with the bars scaled so the biggest one fills up the room left in between
+fn histogram(bars: &[(String, f32)], ctx: &Context, theme: &Theme) -> Vec<String> {
+    let (filled, tip) = (theme.glyphs.bar_filled, theme.glyphs.bar_tip);
+    let values = bars
+        .iter()
+        .map(|(_, value)| format_value(*value))
+        .collect::<Vec<_>>();
+    let value_width = values.iter().map(|value| value.chars().count()).max();
+    let value_width = value_width.unwrap_or_default();
+    // labels get at most a third of the room
+    let label_width = bars.iter().map(|(label, _)| label.chars().count()).max();
+    let label_width = label_width.unwrap_or_default();
+    let label_width = match ctx.width {
+        Some(width) => label_width.min(width / 3),
+        None => label_width,
+    };
+    let bar_width = match ctx.remaining(label_width + value_width + 2) {
+        Some(room) => room.min(ctx.bar_width()).max(1),
+        None => ctx.bar_width(),
+    };
+
+    let max = bars.iter().map(|(_, value)| *value).fold(0.0, f32::max);
+    bars.iter()
+        .zip(values)
+        .map(|((label, value), shown)| {
+            let label = ellipsize(label, Some(label_width), theme.glyphs.ellipsis);
+            let chunks = match max > 0.0 {
+                true => (value.max(0.0) / max * bar_width as f32 * 2.0).round() as usize,
+                false => 0,
+            };
+            let mut bar = filled.repeat(chunks / 2);
+            if chunks % 2 == 1 {
+                bar.push_str(tip);
+            }
+            let pad = bar_width.saturating_sub(bar.chars().count());
+            let bar = theme.paint(&bar, theme.colors.bar);
+            format!("{label:label_width$} {bar}{:pad$} {shown}", "")
+        })
+        .collect()
+}
+
+/// `12` for whole numbers, `3.5` for everything else
+fn format_value(value: f32) -> String {
+    match value.fract() == 0.0 && value.abs() < 1e9 {
+        true => format!("{value:.0}"),
+        false => format!("{value:.1}"),
+    }
+}
+
+/// a bar filled in with one colored segment after another, each taking up its share of `width`
+fn segmented(
+    segments: &[(f32, Option<Color>)],
+    width: usize,
+    center_msg: String,
+    theme: &Theme,
+) -> String {
+    overlay(width, &center_msg, theme, |width| {
+        // where each segment ends, rounded so they never add up to more than the whole bar
+        let mut filled = 0.0;
+        let ends = segments
+            .iter()
+            .map(|(share, _)| {
+                filled += share.max(0.0);
+                ((filled.min(1.0) * width as f32).round() as usize).min(width)
+            })
+            .collect::<Vec<_>>();
+
+        (0..width)
+            .map(|n| {
+                let segment = ends.iter().zip(segments).find(|(&end, _)| n < end);
+                match segment {
+                    Some((_, &(_, color))) => (theme.glyphs.bar_filled, color),
+                    None => (" ", None),
+                }
+            })
+            .collect()
+    })
+}
+
+/// a bar with a block bouncing from one end to the other, `tick` steps in
+fn bounce(tick: usize, width: usize, center_msg: String, theme: &Theme) -> String {
+    overlay(width, &center_msg, theme, |width| {
+        let block = (width / 4).max(1);
+        // there and back again
+        let steps = (width - block).max(1);
+        let step = tick % (steps * 2);
+        let start = match step > steps {
+            true => steps * 2 - step,
+            false => step,
+        };
+
+        (0..width)
+            .map(|n| match (start..start + block).contains(&n) {
+                true => (theme.glyphs.bar_filled, theme.colors.bar),
+                false => (" ", None),
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::strip_escapes;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_rate() {
+        let start = Instant::now();
+        let mut rate = Rate::default();
+        rate.start(start);
+        rate.sample(start + Duration::from_secs(1), 10.0);
+        assert_eq!(rate.per_sec(), Some(10.0));
+
+        // too close to the last one to count yet
+        rate.sample(start + Duration::from_millis(1010), 30.0);
+        assert_eq!(rate.per_sec(), Some(10.0));
+
+        // speeding up moves the rate towards the new speed without jumping all the way there
+        rate.sample(start + Duration::from_secs(2), 40.0);
+        let per_sec = rate.per_sec().unwrap();
+        assert!(10.0 < per_sec && per_sec < 30.0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let theme = Theme::NO_COLOR;
+        let mut ctx = Context::new(time::SystemTime::now(), None, &theme);
+        let start = ctx.now;
+
+        let mut widget = Widget::new_progress("repos", 0, 100);
+        widget.started = Some(start);
+        widget.rate.start(start);
+        widget.update_discrete_progress(40);
+        widget.rate.sample(start + Duration::from_secs(4), 40.0);
+
+        ctx.now = start + Duration::from_secs(4);
+        let line = strip_escapes(&widget.render(&ctx)[0]);
+        assert!(line.ends_with("]    4.0s    10 items/s    ETA 6.0s repos"));
+
+        widget.finished = Some(start + Duration::from_secs(130));
+        widget.set_done();
+        assert_eq!(widget.stats(ctx.now), "  2m10s   0.8 items/s            ");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h02m");
+    }
+
+    #[test]
+    fn test_bounce() {
+        let theme = Theme::NO_COLOR;
+        let frames = (0..8)
+            .map(|tick| bounce(tick, 8, "[1]".to_string(), &theme))
+            .collect::<Vec<_>>();
+        assert_eq!(frames[0], "━━[1]   ");
+        assert_eq!(frames[1], " ━[1]   ");
+        assert_eq!(frames[6], "  [1] ━━");
+        assert_eq!(frames[7], "  [1]━━ ");
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let sparks = Theme::UNICODE.sparks;
+        assert_eq!(sparkline(&[1.0, 2.0, 8.0], 10, sparks), "▁▂█");
+        assert_eq!(sparkline(&[3.0, 3.0], 10, sparks), "▁▁");
+        // pairs get averaged together to fit
+        assert_eq!(sparkline(&[0.0, 2.0, 1.0, 1.0, 7.0, 7.0], 3, sparks), "▁▁█");
+    }
+
+    #[test]
+    fn test_segmented() {
+        let theme = Theme::UNICODE;
+        let segments = [(0.5, Some(Color::Green)), (0.2, Some(Color::Red))];
+        // the message takes on the color of whatever segment is under it
+        assert_eq!(
+            segmented(&segments, 10, "[ab]".to_string(), &theme),
+            format!(
+                "{}{}   ",
+                theme.paint("━━━[a", Some(Color::Green)),
+                theme.paint("b]", Some(Color::Red))
+            )
+        );
+    }
+
+    fn render(widget: &Widget, width: Option<usize>) -> Vec<String> {
+        let theme = Theme::NO_COLOR;
+        widget.render(&Context::new(time::SystemTime::now(), width, &theme))
+    }
+
+    #[test]
+    fn test_counter() {
+        let mut widget = Widget::new_counter("repos", 0, 10);
+        widget.count(Outcome::Succeeded);
+        widget.count(Outcome::Succeeded);
+        assert!(render(&widget, None)[0].contains("[━━━━━  [2✓ of 10]        ]"));
+        assert!(!widget.is_done());
+
+        widget.update_total(4);
+        widget.count(Outcome::Failed);
+        widget.count(Outcome::Skipped);
+        assert!(widget.is_done());
+        assert!(render(&widget, None)[0].contains("[━━━━━[2✓ 1✗ 1↷ of 4]━━━━━]"));
+
+        // a total of 0 hasnt been set yet
+        let mut widget = Widget::new_counter("repos", 0, 0);
+        assert!(!widget.is_done());
+        widget.set_done();
+        assert!(widget.is_done());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut widget = Widget::new_bytes("download", 0, Some(3 * 1024 * 1024));
+        widget.add_bytes(2 * 1024 * 1024);
+        assert!(render(&widget, Some(60))[0].contains("[2.0/3.0 MiB]"));
+        assert_eq!(format_bytes(340.0 * 1024.0), "340.0 KiB");
+        assert_eq!(format_bytes(512.0), "512 B");
+
+        // a short transfer keeps the total it was meant to have
+        widget.set_done();
+        assert!(widget.is_done());
+        assert_eq!(
+            widget.widget,
+            WidgetType::Bytes {
+                transferred: 2 * 1024 * 1024,
+                total: Some(3 * 1024 * 1024)
+            }
+        );
+
+        // one that never said how big it was is however big it turned out to be
+        let mut widget = Widget::new_bytes("download", 0, None);
+        widget.add_bytes(40);
+        widget.set_done();
+        assert_eq!(
+            widget.widget,
+            WidgetType::Bytes {
+                transferred: 40,
+                total: Some(40)
+            }
+        );
+    }
+
+    #[test]
+    fn test_indeterminate() {
+        let mut widget = Widget::new_indeterminate("pages", 0);
+        widget.increment_progress(2);
+        assert!(widget.is_active());
+        assert!(!widget.is_done());
+        assert!(render(&widget, None)[0].contains("[2]"));
+    }
+
+    #[test]
+    fn test_table() {
+        let columns = vec![Column::left("repo"), Column::right("stars")];
+        let mut widget = Widget::new_table("stats", 0, columns);
+        widget.add_row(vec!["tokio".into(), "24301".into()]);
+        widget.add_row(vec!["crossterm".into(), "2873".into()]);
+        widget.set_row(vec!["crossterm".into(), "2874".into()]);
+        assert_eq!(
+            render(&widget, None),
+            [
+                "stats",
+                "repo       stars",
+                "tokio      24301",
+                "crossterm   2874"
+            ]
+        );
+        assert_eq!(widget.state(), State::Done);
+    }
+
+    #[test]
+    fn test_logs() {
+        let mut widget = Widget::new_task("fetch", 0);
+        for line in ["page 1", "page 2", "page 3"] {
+            widget.log(line);
+        }
+        // failed widgets keep showing the end of their log
+        widget.set_error("rate limited");
+        assert_eq!(
+            render(&widget, None)[1..],
+            ["    page 1", "    page 2", "    page 3"]
+        );
+
+        for n in 0..30 {
+            widget.log(format!("line {n}"));
         }
+        assert_eq!(widget.logs.len(), Widget::MAX_LOGS);
+        assert_eq!(widget.logs[0], "line 10");
     }
 
-    let uncolored = format!(
-        "{left:left_width$}{center_msg}{right:right_width$}",
-        right_width = width - left_width - center_width
-    );
-    let left = uncolored.chars().take(chunks + 1).collect::<String>();
-    let right = uncolored.chars().skip(chunks + 1).collect::<String>();
-    format!("{}{}", left.green(), right)
+    #[test]
+    fn test_ellipsize_wide() {
+        // every one of these takes up two columns
+        assert_eq!(ellipsize("日本語のテキスト", Some(7), "…"), "日本語…");
+        assert_eq!(ellipsize("日本語", Some(6), "…"), "日本語");
+        let escaped = ellipsize_escaped("\x1b[31m日本語\x1b[0m", 5, "…");
+        assert_eq!(escaped, "\x1b[31m日本…\x1b[0m");
+    }
 }
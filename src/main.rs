@@ -1,7 +1,7 @@
 #![feature(async_closure)]
 #![feature(adt_const_params)]
 
-use std::{sync::Mutex, time::Duration};
+use std::time::Duration;
 
 use progress_view::{app::{App, UpdateSender}, update::Update, widget::Widget};
 use tokio::{
@@ -15,5 +15,8 @@ async fn update_message(sender: UpdateSender, message: &'static str, time: u64)
 
  fn main() {
     let mut app = App::default();
-    app.run_until_done();
+    app.set_handle_ctrl_c(true);
+    if let Err(aborted) = app.run_until_done() {
+        eprintln!("{aborted}");
+    }
 }